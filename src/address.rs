@@ -0,0 +1,119 @@
+//! The full addresses of OrbitDB databases, `/orbitdb/<cid>/<name>`
+
+use super::*;
+use cid::Cid;
+use serde::{Deserialize, Deserializer, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// The prefix of every OrbitDB address
+const PREFIX: &str = "/orbitdb/";
+
+/// The address of an OrbitDB database, `/orbitdb/<cid>/<name>`, where `<cid>`
+/// is the CID of the database manifest
+///
+/// The CID may be a base58btc CIDv0 (`Qm...`) or a CIDv1 in any multibase
+/// encoding, e.g. `zdpu...` or `bafy...`. Its string, `address.as_ref()`, is
+/// accepted wherever a database name is, opening the database by its address
+/// rather than by the server's own name for it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrbitDbAddress {
+    /// The full address
+    address: String,
+    /// The length of the CID, which follows the prefix
+    root_len: usize,
+}
+
+impl OrbitDbAddress {
+    /// Builds the address of the database `name` whose manifest has the CID `root`
+    pub fn new(root: &str, name: &str) -> Result<Self, Error> {
+        if let Err(err) = Cid::try_from(root) {
+            return Err(Error::InvalidAddress(format!(
+                "`{}` is not a valid CID: {}",
+                root, err
+            )));
+        }
+        if name.is_empty() {
+            return Err(Error::InvalidAddress(String::from(
+                "the database name is empty",
+            )));
+        }
+
+        Ok(OrbitDbAddress {
+            address: format!("{}{}/{}", PREFIX, root, name),
+            root_len: root.len(),
+        })
+    }
+
+    /// The CID of the database manifest, as written in the address
+    pub fn root(&self) -> &str {
+        &self.address[PREFIX.len()..PREFIX.len() + self.root_len]
+    }
+
+    /// The name of the database
+    pub fn name(&self) -> &str {
+        &self.address[PREFIX.len() + self.root_len + 1..]
+    }
+
+    /// The full address
+    pub fn as_str(&self) -> &str {
+        &self.address
+    }
+}
+
+impl FromStr for OrbitDbAddress {
+    type Err = Error;
+
+    fn from_str(address: &str) -> Result<Self, Error> {
+        let rest = match address.strip_prefix(PREFIX) {
+            Some(rest) => rest,
+            None => {
+                return Err(Error::InvalidAddress(format!(
+                    "`{}` does not start with `{}`",
+                    address, PREFIX
+                )))
+            }
+        };
+        match rest.split_once('/') {
+            Some((root, name)) => Self::new(root, name),
+            None => Err(Error::InvalidAddress(format!(
+                "`{}` has no database name",
+                address
+            ))),
+        }
+    }
+}
+
+impl TryFrom<&Address> for OrbitDbAddress {
+    type Error = Error;
+
+    fn try_from(address: &Address) -> Result<Self, Error> {
+        Self::new(&address.root, &address.path)
+    }
+}
+
+impl fmt::Display for OrbitDbAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.address)
+    }
+}
+
+impl AsRef<str> for OrbitDbAddress {
+    fn as_ref(&self) -> &str {
+        &self.address
+    }
+}
+
+impl Serialize for OrbitDbAddress {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.address)
+    }
+}
+
+impl<'de> Deserialize<'de> for OrbitDbAddress {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let address = String::deserialize(deserializer)?;
+        address.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,106 @@
+//! Credentials sent with every request, for servers behind an authenticating proxy
+
+use super::*;
+use futures::future::BoxFuture;
+use std::fmt;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// The credentials a `Client` authenticates its requests with
+pub struct Auth(Credentials);
+
+enum Credentials {
+    /// A static bearer token
+    Bearer(String),
+    /// A username and optional password
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    /// Bearer tokens fetched by a callback
+    Provider {
+        fetch: Box<dyn Fn() -> BoxFuture<'static, Result<String, Error>> + Send + Sync>,
+        /// The last fetched token
+        token: Mutex<Option<String>>,
+    },
+}
+
+impl Auth {
+    /// Authenticates with the bearer token `token`
+    pub fn bearer(token: &str) -> Self {
+        Auth(Credentials::Bearer(token.into()))
+    }
+
+    /// Authenticates with HTTP basic auth
+    pub fn basic(username: &str, password: Option<&str>) -> Self {
+        Auth(Credentials::Basic {
+            username: username.into(),
+            password: password.map(String::from),
+        })
+    }
+
+    /// Authenticates with bearer tokens returned by `fetch`
+    ///
+    /// `fetch` is called for the first request, then again to refresh the
+    /// token whenever the server answers `401 Unauthorized`, the rejected
+    /// request being sent once more with the new token
+    pub fn provider<F, Fut>(fetch: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, Error>> + Send + 'static,
+    {
+        Auth(Credentials::Provider {
+            fetch: Box::new(move || Box::pin(fetch())),
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Whether a rejected token can be replaced by a new one
+    pub(crate) fn refreshable(&self) -> bool {
+        matches!(self.0, Credentials::Provider { .. })
+    }
+
+    /// The value of the `Authorization` header, fetching a token if there is none yet
+    pub(crate) async fn header(&self) -> Result<String, Error> {
+        match &self.0 {
+            Credentials::Bearer(token) => Ok(format!("Bearer {}", token)),
+            Credentials::Basic { username, password } => {
+                let credentials = format!("{}:{}", username, password.as_deref().unwrap_or(""));
+                Ok(format!("Basic {}", base64::encode(credentials)))
+            }
+            Credentials::Provider { token, .. } => {
+                let current = token.lock().unwrap().clone();
+                let current = match current {
+                    Some(current) => current,
+                    None => self.refresh().await?,
+                };
+                Ok(format!("Bearer {}", current))
+            }
+        }
+    }
+
+    /// Fetches a new token, for credentials that are refreshable
+    pub(crate) async fn refresh(&self) -> Result<String, Error> {
+        match &self.0 {
+            Credentials::Provider { fetch, token } => {
+                let fetched = fetch().await?;
+                *token.lock().unwrap() = Some(fetched.clone());
+                Ok(fetched)
+            }
+            _ => Err(Error::Transport(
+                "the credentials cannot be refreshed".into(),
+            )),
+        }
+    }
+}
+
+/// Keeps the secrets out of logs
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Credentials::Bearer(_) => f.write_str("Auth::Bearer(..)"),
+            Credentials::Basic { username, .. } => write!(f, "Auth::Basic({}, ..)", username),
+            Credentials::Provider { .. } => f.write_str("Auth::Provider(..)"),
+        }
+    }
+}
@@ -1,4 +1,7 @@
-use orbit_db_http_client::{Client, DatabaseType, Query};
+use futures::StreamExt;
+use orbit_db_http_client::{
+    Auth, Client, Comparison, DatabaseType, EventKind, IteratorOptions, OpenOptions, Query,
+};
 use structopt::StructOpt;
 
 /// A client written in Rust for OrbitDB's REST server
@@ -9,6 +12,18 @@ struct CmdLine {
     #[structopt(short, long)]
     server_url: url::Url,
 
+    /// Bearer token authenticating every request
+    #[structopt(long, env = "ORBITDB_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+
+    /// Username authenticating every request with basic auth
+    #[structopt(long, env = "ORBITDB_USERNAME")]
+    username: Option<String>,
+
+    /// Password of the basic auth username
+    #[structopt(long, env = "ORBITDB_PASSWORD", hide_env_values = true)]
+    password: Option<String>,
+
     /// Command for client to communicate with REST API
     #[structopt(subcommand)]
     cmd: Command,
@@ -36,6 +51,21 @@ enum Command {
     GetDbIterator {
         dbname: String,
         limit: Option<i64>,
+        /// Only items after the item with this hash
+        #[structopt(long)]
+        gt: Option<String>,
+        /// Only the item with this hash and those after it
+        #[structopt(long)]
+        gte: Option<String>,
+        /// Only items before the item with this hash
+        #[structopt(long)]
+        lt: Option<String>,
+        /// Only the item with this hash and those before it
+        #[structopt(long)]
+        lte: Option<String>,
+        /// Reverses the order of the items
+        #[structopt(long)]
+        reverse: bool,
     },
     /// Gets the database information
     GetDbIndex {
@@ -49,10 +79,42 @@ enum Command {
         dbname: String,
         dbtype: DatabaseType,
     },
-    /// Applies a query to the specified database
-    // TODO: add query argument
+    /// Opens a database by name or full OrbitDB address, without creating it unless asked to
+    OpenDb {
+        dbname: String,
+        /// Creates the database if it does not exist, requiring --dbtype
+        #[structopt(long)]
+        create: bool,
+        /// The type of the database
+        #[structopt(long)]
+        dbtype: Option<DatabaseType>,
+        /// Only opens the database if it is stored locally
+        #[structopt(long)]
+        local_only: bool,
+        /// Does not replicate the database with peers
+        #[structopt(long)]
+        no_replicate: bool,
+        /// The number of entries to load, -1 loading all of them
+        #[structopt(long)]
+        max_history: Option<i64>,
+        /// The directory the server stores the database in
+        #[structopt(long)]
+        directory: Option<String>,
+        /// Waits for the database to sync with its peers
+        #[structopt(long)]
+        sync: bool,
+    },
+    /// Applies a query to the specified database (all documents if no comparison is given)
     DbQuery {
         dbname: String,
+        /// The property compared (`_id` if not specified)
+        #[structopt(short, long)]
+        propname: Option<String>,
+        /// The comparison: eq, ne, gt, lt, gte, lte, mod, range or all
+        #[structopt(short, long)]
+        comp: Option<Comparison>,
+        /// The json values compared against
+        values: Vec<serde_json::Value>,
     },
     /// Adds an entry to the specified EventLog or Feed
     DbAdd {
@@ -69,6 +131,13 @@ enum Command {
         dbname: String,
         value: Option<u64>,
     },
+    /// Prints the events of the specified database as they happen
+    Subscribe {
+        dbname: String,
+        /// The events to subscribe to, e.g. write or replicated
+        #[structopt(required = true)]
+        events: Vec<EventKind>,
+    },
     // Grants the specified id write access for that database
     GrantWriteAccess {
         dbname: String,
@@ -85,12 +154,22 @@ enum Command {
     },
 }
 
-#[async_attributes::main]
-async fn main() -> Result<(), surf::Exception> {
+#[cfg_attr(feature = "tokio", tokio::main)]
+#[cfg_attr(
+    all(not(feature = "tokio"), feature = "async-std"),
+    async_attributes::main
+)]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Get the arguments passed through the command line
     let args = CmdLine::from_args();
     // Create client instance with provided server url
-    let client = Client::new(args.server_url.clone());
+    let mut builder = Client::builder(args.server_url.clone());
+    if let Some(token) = &args.token {
+        builder = builder.auth(Auth::bearer(token));
+    } else if let Some(username) = &args.username {
+        builder = builder.auth(Auth::basic(username, args.password.as_deref()));
+    }
+    let client = builder.build()?;
     // Make HTTP requests
     // Run corresponding client method based on provided subcommand
     match args.cmd {
@@ -107,11 +186,27 @@ async fn main() -> Result<(), surf::Exception> {
             dbg!(value);
         }
         Command::GetDbItem { dbname, item } => {
-            let item = client.get_db_item(&dbname, &item).await?;
+            let item: Vec<serde_json::Value> = client.get_db_item(&dbname, &item).await?;
             dbg!(item);
         }
-        Command::GetDbIterator { dbname, limit } => {
-            let iter = client.get_db_iterator(&dbname, limit).await?;
+        Command::GetDbIterator {
+            dbname,
+            limit,
+            gt,
+            gte,
+            lt,
+            lte,
+            reverse,
+        } => {
+            let options = IteratorOptions {
+                limit,
+                gt,
+                gte,
+                lt,
+                lte,
+                reverse: reverse.then_some(true),
+            };
+            let iter: Vec<serde_json::Value> = client.get_db_iterator(&dbname, options).await?;
             dbg!(iter);
         }
         Command::GetDbIndex { dbname } => {
@@ -126,14 +221,43 @@ async fn main() -> Result<(), surf::Exception> {
             let value = client.create_db(&dbname, dbtype, None, false).await?;
             dbg!(value);
         }
-        Command::DbQuery { dbname } => {
-            let query = Query {
-                propname: None,
-                comp: None,
-                values: vec![],
+        Command::OpenDb {
+            dbname,
+            create,
+            dbtype,
+            local_only,
+            no_replicate,
+            max_history,
+            directory,
+            sync,
+        } => {
+            // Flags not passed are left to the server's defaults
+            let options = OpenOptions {
+                create: create.then_some(true),
+                local_only: local_only.then_some(true),
+                replicate: no_replicate.then_some(false),
+                max_history,
+                directory,
+                sync: sync.then_some(true),
+                ..OpenOptions::new()
+            };
+            let options = match dbtype {
+                Some(dbtype) => options.dbtype(dbtype),
+                None => options,
             };
 
-            let items = client.db_query(&dbname, query).await?;
+            let value = client.open_db(&dbname, options).await?;
+            dbg!(value);
+        }
+        Command::DbQuery {
+            dbname,
+            propname,
+            comp,
+            values,
+        } => {
+            let query = Query::new(propname, comp, values)?;
+
+            let items: Vec<serde_json::Value> = client.db_query(&dbname, query).await?;
             dbg!(items);
         }
         Command::DbAdd { dbname, entry } => {
@@ -152,9 +276,14 @@ async fn main() -> Result<(), surf::Exception> {
             let hash = client.grant_write_access(&dbname, id).await?;
             dbg!(hash);
         }
+        Command::Subscribe { dbname, events } => {
+            let mut events = Box::pin(client.subscribe(&dbname, &events)?);
+            while let Some(event) = events.next().await {
+                dbg!(event?);
+            }
+        }
         Command::DeleteDb { dbname } => {
-            let empty_obj = client.delete_db(&dbname).await?;
-            dbg!(empty_obj);
+            client.delete_db(&dbname).await?;
         }
         Command::DeleteDbItem { dbname, item } => {
             let hash = client.delete_db_item(&dbname, &item).await?;
@@ -0,0 +1,267 @@
+//! A synchronous client, for code not running on an async runtime
+//!
+//! Each `blocking::Client` drives its requests to completion on a runtime
+//! of its own, so its methods must not be called from within an async task
+
+use super::*;
+use futures::{Stream, StreamExt};
+use runtime::Runtime;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::pin::Pin;
+use url::Url;
+
+mod handles;
+
+pub use handles::{Counter, DocStore, EventLog, Feed, KeyValue};
+
+/// The structure used for making blocking requests to an OrbitDB REST API
+pub struct Client {
+    /// The async client making the requests
+    inner: super::Client,
+    /// The runtime the requests are run on
+    runtime: Runtime,
+}
+
+/// The items of a stream, each received by blocking on the client's runtime
+struct BlockingIter<'a, S> {
+    runtime: &'a Runtime,
+    stream: Pin<Box<S>>,
+}
+
+impl<'a, S: Stream> Iterator for BlockingIter<'a, S> {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+impl Client {
+    /// The constructor, sending requests through the default transport
+    ///
+    /// Fails if the client's runtime cannot be started
+    #[cfg(any(feature = "surf", feature = "reqwest", feature = "hyper"))]
+    pub fn new(base_url: Url) -> Result<Self, Error> {
+        Self::from_async(super::Client::new(base_url))
+    }
+
+    /// Creates a client sending its requests through `transport`
+    ///
+    /// Fails if the client's runtime cannot be started
+    pub fn with_transport(
+        base_url: Url,
+        transport: impl Transport + 'static,
+    ) -> Result<Self, Error> {
+        Self::from_async(super::Client::with_transport(base_url, transport))
+    }
+
+    /// Creates a blocking client making its requests with the async `client`
+    ///
+    /// Fails if the client's runtime cannot be started
+    pub fn from_async(client: super::Client) -> Result<Self, Error> {
+        Ok(Client {
+            inner: client,
+            runtime: Runtime::new()?,
+        })
+    }
+
+    /// Turns `stream` into an iterator blocking on each of its items
+    fn iter<'a, S: Stream + 'a>(&'a self, stream: S) -> impl Iterator<Item = S::Item> + 'a {
+        BlockingIter {
+            runtime: &self.runtime,
+            stream: Box::pin(stream),
+        }
+    }
+
+    /// Gets a handle to the EventLog `dbname`, whose entries are of type `T`
+    pub fn eventlog<T>(&self, dbname: &str) -> EventLog<'_, T> {
+        EventLog::new(self, dbname)
+    }
+
+    /// Gets a handle to the Feed `dbname`, whose entries are of type `T`
+    pub fn feed<T>(&self, dbname: &str) -> Feed<'_, T> {
+        Feed::new(self, dbname)
+    }
+
+    /// Gets a handle to the DocStore `dbname`, whose documents are of type `T`
+    pub fn docstore<T>(&self, dbname: &str) -> DocStore<'_, T> {
+        DocStore::new(self, dbname)
+    }
+
+    /// Gets a handle to the KeyValue store `dbname`, whose values are of type `T`
+    pub fn keyvalue<T>(&self, dbname: &str) -> KeyValue<'_, T> {
+        KeyValue::new(self, dbname)
+    }
+
+    /// Gets a handle to the Counter `dbname`
+    pub fn counter(&self, dbname: &str) -> Counter<'_> {
+        Counter::new(self, dbname)
+    }
+
+    /// Makes a GET request to `self.base_url/dbs`,
+    /// returning a hashmap of databases
+    pub fn get_dbs(&self) -> Result<HashMap<String, Database>, Error> {
+        self.runtime.block_on(self.inner.get_dbs())
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname`,
+    /// returning the database structure on success
+    pub fn get_db(&self, dbname: &str) -> Result<Database, Error> {
+        self.runtime.block_on(self.inner.get_db(dbname))
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname/value`,
+    /// returning the counter's value on success
+    pub fn get_counter_value(&self, dbname: &str) -> Result<u64, Error> {
+        self.runtime.block_on(self.inner.get_counter_value(dbname))
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname/:item`,
+    /// returning the database's record identified by `:item` on
+    /// success
+    pub fn get_db_item<T: DeserializeOwned>(
+        &self,
+        dbname: &str,
+        item: &str,
+    ) -> Result<Vec<T>, Error> {
+        self.runtime.block_on(self.inner.get_db_item(dbname, item))
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname/iterator`,
+    /// returning the items of an EventLog or Feed selected by `options`
+    /// on success
+    ///
+    /// `options` may simply be a limit, `None` returning every item
+    pub fn get_db_iterator<T: DeserializeOwned>(
+        &self,
+        dbname: &str,
+        options: impl Into<IteratorOptions>,
+    ) -> Result<Vec<T>, Error> {
+        self.runtime
+            .block_on(self.inner.get_db_iterator(dbname, options))
+    }
+
+    /// Iterates over every item of an EventLog or Feed, newest first,
+    /// making GET requests to `self.base_url/db/:dbname/iterator` for one
+    /// page of `page_size` items at a time
+    pub fn stream_log<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        dbname: &str,
+        page_size: usize,
+    ) -> impl Iterator<Item = Result<T, Error>> + 'a {
+        self.iter(self.inner.stream_log(dbname, page_size))
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname/value`,
+    /// returning the database information on success
+    pub fn get_db_index(&self, dbname: &str) -> Result<Value, Error> {
+        self.runtime.block_on(self.inner.get_db_index(dbname))
+    }
+
+    /// Makes a GET request to `self.base_url/identity`,
+    /// returning the identity structure on success
+    pub fn get_identity(&self) -> Result<Identity, Error> {
+        self.runtime.block_on(self.inner.get_identity())
+    }
+
+    /// Makes a GET request to `self.base_url/db/:dbname/events/:events`,
+    /// returning an iterator over the database's events of the given kinds
+    ///
+    /// Each call to `next` blocks until the next event is received
+    pub fn subscribe(
+        &self,
+        dbname: &str,
+        events: &[EventKind],
+    ) -> Result<impl Iterator<Item = Result<Event, Error>> + '_, Error> {
+        Ok(self.iter(self.inner.subscribe(dbname, events)?))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname`,
+    /// sending the specified db type and returning the
+    /// database structure on success
+    pub fn create_db(
+        &self,
+        dbname: &str,
+        dbtype: DatabaseType,
+        ac: Option<AccessController>,
+        overwrite: bool,
+    ) -> Result<Database, Error> {
+        self.runtime
+            .block_on(self.inner.create_db(dbname, dbtype, ac, overwrite))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname`,
+    /// opening the database with the given options and returning
+    /// the database structure on success
+    pub fn open_db(&self, dbname: &str, options: OpenOptions) -> Result<Database, Error> {
+        self.runtime.block_on(self.inner.open_db(dbname, options))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname/query`,
+    /// sending the query to be interpretted and processed returning
+    /// the items on success
+    pub fn db_query<T: DeserializeOwned>(
+        &self,
+        dbname: &str,
+        query: Query,
+    ) -> Result<Vec<T>, Error> {
+        self.runtime.block_on(self.inner.db_query(dbname, query))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname/add`,
+    /// sending the entry to be added to the EventLog or Feed and returning
+    /// the hash on success
+    pub fn db_add<T: Serialize + ?Sized>(&self, dbname: &str, entry: &T) -> Result<Hash, Error> {
+        self.runtime.block_on(self.inner.db_add(dbname, entry))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname/put`,
+    /// sending the record to be added to the database and returning
+    /// the hash on success
+    pub fn db_put<T: Serialize + ?Sized>(&self, dbname: &str, record: &T) -> Result<Hash, Error> {
+        self.runtime.block_on(self.inner.db_put(dbname, record))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname/inc`,
+    /// to increment the counter database by 1 and returning
+    /// the hash on success
+    pub fn inc_counter_value(&self, dbname: &str, amount: Option<u64>) -> Result<Hash, Error> {
+        self.runtime
+            .block_on(self.inner.inc_counter_value(dbname, amount))
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname/access/write`,
+    /// to add the id to the list of peers who have writing access
+    /// for that database, returning the hash on success
+    pub fn grant_write_access(&self, dbname: &str, id: String) -> Result<Hash, Error> {
+        self.runtime
+            .block_on(self.inner.grant_write_access(dbname, id))
+    }
+
+    /// Makes a DELETE request to `self.base_url/db/:dbname`,
+    /// to delete the specified database
+    pub fn delete_db(&self, dbname: &str) -> Result<(), Error> {
+        self.runtime.block_on(self.inner.delete_db(dbname))
+    }
+
+    /// Makes a DELETE request to `self.base_url/db/:dbname/:item`,
+    /// to delete the specified item from the database and returning
+    /// the hash on success
+    pub fn delete_db_item(&self, dbname: &str, item: &str) -> Result<Hash, Error> {
+        self.runtime
+            .block_on(self.inner.delete_db_item(dbname, item))
+    }
+
+    /// Makes a request of type `rtype` to `self.base_url/:path`, sending the
+    /// `body` json value, if any, and returning the decoded response along
+    /// with its status
+    pub fn request<T: DeserializeOwned>(
+        &self,
+        rtype: RequestType,
+        path: &str,
+        body: &Value,
+    ) -> Result<ApiResponse<T>, Error> {
+        self.runtime.block_on(self.inner.request(rtype, path, body))
+    }
+}
@@ -0,0 +1,210 @@
+//! Blocking versions of the typed database handles, running the async
+//! handles on their client's runtime
+
+use super::*;
+
+/// A handle to an EventLog database whose entries are of type `T`
+pub struct EventLog<'a, T> {
+    client: &'a Client,
+    inner: super::super::EventLog<'a, T>,
+}
+
+impl<'a, T> EventLog<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        EventLog {
+            client,
+            inner: client.inner.eventlog(dbname),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        self.inner.dbname()
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> EventLog<'a, T> {
+    /// Adds an entry to the log, returning its hash
+    pub fn add(&self, entry: &T) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.add(entry))
+    }
+
+    /// Gets the entry identified by `hash`
+    pub fn get(&self, hash: &str) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.get(hash))
+    }
+
+    /// Gets the entries of the log selected by `options`
+    pub fn iterator(&self, options: impl Into<IteratorOptions>) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.iterator(options))
+    }
+
+    /// Iterates over every entry of the log, newest first, fetching
+    /// `page_size` entries at a time
+    pub fn stream(&self, page_size: usize) -> impl Iterator<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        self.client.iter(self.inner.stream(page_size))
+    }
+}
+
+/// A handle to a Feed database whose entries are of type `T`
+pub struct Feed<'a, T> {
+    client: &'a Client,
+    inner: super::super::Feed<'a, T>,
+}
+
+impl<'a, T> Feed<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        Feed {
+            client,
+            inner: client.inner.feed(dbname),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        self.inner.dbname()
+    }
+
+    /// Removes the entry identified by `hash`, returning the hash of the removal
+    pub fn remove(&self, hash: &str) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.remove(hash))
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Feed<'a, T> {
+    /// Adds an entry to the feed, returning its hash
+    pub fn add(&self, entry: &T) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.add(entry))
+    }
+
+    /// Gets the entry identified by `hash`
+    pub fn get(&self, hash: &str) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.get(hash))
+    }
+
+    /// Gets the entries of the feed selected by `options`
+    pub fn iterator(&self, options: impl Into<IteratorOptions>) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.iterator(options))
+    }
+
+    /// Iterates over every entry of the feed, newest first, fetching
+    /// `page_size` entries at a time
+    pub fn stream(&self, page_size: usize) -> impl Iterator<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        self.client.iter(self.inner.stream(page_size))
+    }
+}
+
+/// A handle to a DocStore database whose documents are of type `T`
+pub struct DocStore<'a, T> {
+    client: &'a Client,
+    inner: super::super::DocStore<'a, T>,
+}
+
+impl<'a, T> DocStore<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        DocStore {
+            client,
+            inner: client.inner.docstore(dbname),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        self.inner.dbname()
+    }
+
+    /// Deletes the document indexed by `key`, returning the hash of the deletion
+    pub fn delete(&self, key: &str) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.delete(key))
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> DocStore<'a, T> {
+    /// Puts a document into the store, returning its hash
+    pub fn put(&self, document: &T) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.put(document))
+    }
+
+    /// Gets the documents indexed by `key`
+    pub fn get(&self, key: &str) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.get(key))
+    }
+
+    /// Gets the documents matching `query`
+    pub fn query(&self, query: Query) -> Result<Vec<T>, Error> {
+        self.client.runtime.block_on(self.inner.query(query))
+    }
+}
+
+/// A handle to a KeyValue database whose values are of type `T`
+pub struct KeyValue<'a, T> {
+    client: &'a Client,
+    inner: super::super::KeyValue<'a, T>,
+}
+
+impl<'a, T> KeyValue<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        KeyValue {
+            client,
+            inner: client.inner.keyvalue(dbname),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        self.inner.dbname()
+    }
+
+    /// Deletes the value stored under `key`, returning the hash of the deletion
+    pub fn delete(&self, key: &str) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.delete(key))
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> KeyValue<'a, T> {
+    /// Stores `value` under `key`, returning the hash of the entry
+    pub fn put(&self, key: &str, value: &T) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.put(key, value))
+    }
+
+    /// Gets the value stored under `key`, if any
+    pub fn get(&self, key: &str) -> Result<Option<T>, Error> {
+        self.client.runtime.block_on(self.inner.get(key))
+    }
+}
+
+/// A handle to a Counter database
+pub struct Counter<'a> {
+    client: &'a Client,
+    inner: super::super::Counter<'a>,
+}
+
+impl<'a> Counter<'a> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        Counter {
+            client,
+            inner: client.inner.counter(dbname),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        self.inner.dbname()
+    }
+
+    /// Gets the counter's value
+    pub fn value(&self) -> Result<u64, Error> {
+        self.client.runtime.block_on(self.inner.value())
+    }
+
+    /// Increments the counter by `amount` (1 if not specified), returning the hash
+    pub fn inc(&self, amount: Option<u64>) -> Result<Hash, Error> {
+        self.client.runtime.block_on(self.inner.inc(amount))
+    }
+}
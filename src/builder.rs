@@ -0,0 +1,255 @@
+//! Configuration of a `Client` beyond its base url
+
+use super::*;
+use futures::stream::{self, StreamExt};
+use sender::Sender;
+use std::sync::Arc;
+use std::time::Duration;
+use transport::{BodyStream, Request, Response, StreamingResponse, TlsConfig};
+use url::Url;
+
+/// Builds a `Client` with timeouts, default headers, a user agent or a base path
+pub struct ClientBuilder {
+    base_url: Url,
+    base_path: Option<String>,
+    transport: Option<Arc<dyn Transport>>,
+    headers: Vec<(String, String)>,
+    user_agent: Option<String>,
+    timeouts: Timeouts,
+    auth: Option<Auth>,
+    tls: Option<TlsConfig>,
+    retry: Option<RetryPolicy>,
+    middleware: Vec<Arc<dyn Middleware>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+}
+
+/// The time limits applied to each request
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Timeouts {
+    /// The time allowed to connect and receive the response headers
+    pub(crate) connect: Option<Duration>,
+    /// The time allowed between two chunks of the response body
+    pub(crate) read: Option<Duration>,
+    /// The time allowed for the whole request, body included
+    pub(crate) overall: Option<Duration>,
+}
+
+impl ClientBuilder {
+    pub(crate) fn new(base_url: Url) -> Self {
+        ClientBuilder {
+            base_url,
+            base_path: None,
+            transport: None,
+            headers: vec![],
+            user_agent: None,
+            timeouts: Timeouts::default(),
+            auth: None,
+            tls: None,
+            retry: None,
+            middleware: vec![],
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Sends the requests through `transport` instead of the default transport
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Limits the time to connect and receive the response headers
+    ///
+    /// For event subscriptions, this bounds each (re)connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.connect = Some(timeout);
+        self
+    }
+
+    /// Limits the time to wait for each chunk of a response body
+    ///
+    /// For event subscriptions, this bounds the time between two events
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.read = Some(timeout);
+        self
+    }
+
+    /// Limits the time of a whole request, from connecting to receiving
+    /// the response body
+    ///
+    /// Event subscriptions are not bound by it
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeouts.overall = Some(timeout);
+        self
+    }
+
+    /// Adds a header sent with every request
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request
+    pub fn user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Authenticates every request with `auth`
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Connects with the TLS settings `tls`, e.g. to trust the self-signed
+    /// certificate of a development server
+    ///
+    /// Only the reqwest and hyper transports support TLS settings, reqwest's
+    /// being used if both are enabled
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Retries the requests failing with transient errors according to `policy`
+    ///
+    /// Each attempt is bound by the time limits on its own. Event
+    /// subscriptions are not retried, as they reconnect by themselves
+    pub fn retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Passes every request and response through `middleware`, after the
+    /// middleware added before it
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Records the traffic of the client in `metrics`
+    #[cfg(feature = "metrics")]
+    pub fn metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Prefixes the path of every request with `base_path`, e.g. for a
+    /// server behind a reverse proxy
+    ///
+    /// The prefix is relative to the base url, `/api/` and `api`
+    /// being equivalent
+    pub fn base_path(mut self, base_path: &str) -> Self {
+        self.base_path = Some(base_path.into());
+        self
+    }
+
+    /// Creates the client
+    ///
+    /// Fails if no transport is given while none is enabled by default, or
+    /// if the TLS settings are invalid or cannot be applied to the transport
+    pub fn build(self) -> Result<Client, Error> {
+        let mut base_url = self.base_url;
+        if let Some(base_path) = self.base_path {
+            let base_path = base_path.trim_matches('/');
+            if !base_path.is_empty() {
+                let path = format!("{}/{}", base_url.path().trim_end_matches('/'), base_path);
+                base_url.set_path(&path);
+            }
+        }
+
+        let mut headers = self.headers;
+        if let Some(user_agent) = self.user_agent {
+            headers.retain(|(name, _)| !name.eq_ignore_ascii_case("User-Agent"));
+            headers.push(("User-Agent".into(), user_agent));
+        }
+
+        let transport = match (self.transport, self.tls) {
+            (Some(_), Some(_)) => {
+                return Err(Error::Config(
+                    "TLS settings cannot be applied to a given transport".into(),
+                ))
+            }
+            (None, Some(tls)) => transport::tls_transport(&tls)?,
+            (Some(transport), None) => transport,
+            #[cfg(any(feature = "surf", feature = "reqwest", feature = "hyper"))]
+            (None, None) => transport::default_transport(),
+            #[cfg(not(any(feature = "surf", feature = "reqwest", feature = "hyper")))]
+            (None, None) => return Err(Error::Config("no transport is enabled".into())),
+        };
+
+        let sender = Sender {
+            transport,
+            headers,
+            timeouts: self.timeouts,
+            auth: self.auth,
+            retry: self.retry,
+            middleware: self.middleware,
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics,
+        };
+        Ok(Client::from_parts(base_url, sender))
+    }
+
+    /// Creates a blocking client
+    ///
+    /// Fails as `build` does, or if the client's runtime cannot be started
+    #[cfg(feature = "blocking")]
+    pub fn build_blocking(self) -> Result<blocking::Client, Error> {
+        blocking::Client::from_async(self.build()?)
+    }
+}
+
+impl Timeouts {
+    /// Sends `request` through `transport`, within the time limits
+    pub(crate) async fn send(
+        self,
+        transport: &dyn Transport,
+        request: Request,
+    ) -> Result<Response, Error> {
+        runtime::timeout(self.overall, async {
+            // The body is only streamed when its chunks must be timed
+            if self.connect.is_none() && self.read.is_none() {
+                return transport.send(request).await;
+            }
+            self.send_streaming(transport, request)
+                .await?
+                .into_response()
+                .await
+        })
+        .await?
+    }
+
+    /// Sends `request` through `transport`, within the connect and read time limits
+    pub(crate) async fn send_streaming(
+        self,
+        transport: &dyn Transport,
+        request: Request,
+    ) -> Result<StreamingResponse, Error> {
+        let response = runtime::timeout(self.connect, transport.send_streaming(request)).await??;
+
+        Ok(StreamingResponse {
+            body: self.read(response.body),
+            ..response
+        })
+    }
+
+    /// Fails the body stream if one of its chunks takes longer than the read timeout
+    fn read(self, body: BodyStream) -> BodyStream {
+        let read = match self.read {
+            Some(read) => read,
+            None => return body,
+        };
+
+        let body = stream::unfold(Some(body), move |body| async move {
+            let mut body = body?;
+            match runtime::timeout(Some(read), body.next()).await {
+                Ok(Some(chunk)) => Some((chunk, Some(body))),
+                Ok(None) => None,
+                Err(err) => Some((Err(err), None)),
+            }
+        });
+        Box::pin(body)
+    }
+}
@@ -1,77 +1,110 @@
 use super::*;
-use serde::Deserialize;
+use futures::Stream;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use sender::Sender;
+use serde::de::DeserializeOwned;
 use serde_json::{json, to_value, Value};
 use std::collections::HashMap;
-use surf::Exception;
+use std::sync::Arc;
 use url::Url;
 
+/// The characters escaped in a database name or item key, `/` included so
+/// that a full OrbitDB address or a key holding slashes stays a single path segment
+const SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// The path of the database `dbname`, relative to the base url
+fn db_path(dbname: &str) -> String {
+    format!("db/{}", utf8_percent_encode(dbname, SEGMENT))
+}
+
+/// The path of the item `item` of the database `dbname`, relative to the base url
+fn item_path(dbname: &str, item: &str) -> String {
+    format!("{}/{}", db_path(dbname), utf8_percent_encode(item, SEGMENT))
+}
+
 /// The structure used for making requests to an OrbitDB REST API
 pub struct Client {
     /// OrbitDB REST server url
     base_url: Url,
-}
-
-/// The information to uniquely identify the OrbitDB instance and sign its entries
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Identity {
-    id: String,
-    public_key: String,
-    signatures: Signatures,
-    r#type: String,
-}
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Signatures {
-    id: String,
-    public_key: String,
-}
-/// The information pertaining to an OrbitDB database
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Database {
-    address: Address,
-    dbname: String,
-    id: String,
-    options: Options,
-    can_append: bool,
-    write: Vec<String>,
-    r#type: String,
-    capabilities: Vec<String>,
-}
-#[derive(Debug, Deserialize)]
-pub struct Address {
-    root: String,
-    path: String,
-}
-#[derive(Debug, Deserialize)]
-pub struct Hash {
-    hash: String,
-}
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct Options {
-    create: bool,
-    index_by: Option<String>,
-    local_only: bool,
-    max_history: i64,
-    overwrite: bool,
-    replicate: bool,
+    /// Sends the HTTP requests through the client's transport
+    sender: Arc<Sender>,
 }
 
 impl Client {
-    /// The constructor
+    /// The constructor, sending requests through the default transport
+    #[cfg(any(feature = "surf", feature = "reqwest", feature = "hyper"))]
     pub fn new(base_url: Url) -> Self {
-        Client { base_url }
+        Self::from_parts(base_url, Sender::new(transport::default_transport()))
+    }
+
+    /// Creates a client sending its requests through `transport`
+    pub fn with_transport(base_url: Url, transport: impl Transport + 'static) -> Self {
+        Self::from_parts(base_url, Sender::new(Arc::new(transport)))
+    }
+
+    /// Starts building a client with more than a base url
+    pub fn builder(base_url: Url) -> ClientBuilder {
+        ClientBuilder::new(base_url)
+    }
+
+    pub(crate) fn from_parts(mut base_url: Url, sender: Sender) -> Self {
+        // Without a trailing slash, the last segment would be replaced by the request paths
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Client {
+            base_url,
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Gets a handle to the EventLog `dbname`, whose entries are of type `T`
+    pub fn eventlog<T>(&self, dbname: &str) -> EventLog<'_, T> {
+        EventLog::new(self, dbname)
+    }
+
+    /// Gets a handle to the Feed `dbname`, whose entries are of type `T`
+    pub fn feed<T>(&self, dbname: &str) -> Feed<'_, T> {
+        Feed::new(self, dbname)
+    }
+
+    /// Gets a handle to the DocStore `dbname`, whose documents are of type `T`
+    pub fn docstore<T>(&self, dbname: &str) -> DocStore<'_, T> {
+        DocStore::new(self, dbname)
+    }
+
+    /// Gets a handle to the KeyValue store `dbname`, whose values are of type `T`
+    pub fn keyvalue<T>(&self, dbname: &str) -> KeyValue<'_, T> {
+        KeyValue::new(self, dbname)
+    }
+
+    /// Gets a handle to the Counter `dbname`
+    pub fn counter(&self, dbname: &str) -> Counter<'_> {
+        Counter::new(self, dbname)
     }
 
     /// Makes a GET request to `self.base_url/dbs`,
     /// returning a hashmap of databases
-    pub async fn get_dbs(&self) -> Result<HashMap<String, Database>, Exception> {
+    pub async fn get_dbs(&self) -> Result<HashMap<String, Database>, Error> {
         let config = RequestConfig {
+            operation: "get_dbs",
+            dbname: None,
             rtype: RequestType::Get,
             path: "dbs".into(),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
@@ -79,11 +112,14 @@ impl Client {
 
     /// Makes a GET request to `self.base_url/db/:dbname`,
     /// returning the database structure on success
-    pub async fn get_db(&self, dbname: &str) -> Result<Database, Exception> {
+    pub async fn get_db(&self, dbname: &str) -> Result<Database, Error> {
         let config = RequestConfig {
+            operation: "get_db",
+            dbname: Some(dbname),
             rtype: RequestType::Get,
-            path: format!("db/{}", &dbname),
+            path: db_path(dbname),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
@@ -91,11 +127,14 @@ impl Client {
 
     /// Makes a GET request to `self.base_url/db/:dbname/value`,
     /// returning the counter's value on success
-    pub async fn get_counter_value(&self, dbname: &str) -> Result<u64, Exception> {
+    pub async fn get_counter_value(&self, dbname: &str) -> Result<u64, Error> {
         let config = RequestConfig {
+            operation: "get_counter_value",
+            dbname: Some(dbname),
             rtype: RequestType::Get,
-            path: format!("db/{}/value", &dbname),
+            path: format!("{}/value", db_path(dbname)),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
@@ -104,40 +143,74 @@ impl Client {
     /// Makes a GET request to `self.base_url/db/:dbname/:item`,
     /// returning the database's record identified by `:item` on
     /// success
-    pub async fn get_db_item(&self, dbname: &str, item: &str) -> Result<Vec<Value>, Exception> {
+    pub async fn get_db_item<T: DeserializeOwned>(
+        &self,
+        dbname: &str,
+        item: &str,
+    ) -> Result<Vec<T>, Error> {
         let config = RequestConfig {
+            operation: "get_db_item",
+            dbname: Some(dbname),
             rtype: RequestType::Get,
-            path: format!("db/{}/{}", &dbname, &item),
+            path: item_path(dbname, item),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
     }
 
     /// Makes a GET request to `self.base_url/db/:dbname/iterator`,
-    /// returning a possibly limited number of items from an EventLog
-    /// or Feed on success
-    pub async fn get_db_iterator(
+    /// returning the items of an EventLog or Feed selected by `options`
+    /// on success
+    ///
+    /// `options` may simply be a limit, `None` returning every item
+    pub async fn get_db_iterator<T: DeserializeOwned>(
         &self,
         dbname: &str,
-        limit: Option<i64>,
-    ) -> Result<Vec<Value>, Exception> {
+        options: impl Into<IteratorOptions>,
+    ) -> Result<Vec<T>, Error> {
+        let mut options = options.into();
+        options.limit.get_or_insert(-1);
+
         let config = RequestConfig {
+            operation: "get_db_iterator",
+            dbname: Some(dbname),
             rtype: RequestType::Get,
-            path: format!("db/{}/iterator", &dbname),
-            body: &json!({ "limit": limit.unwrap_or(-1) }),
+            path: format!("{}/iterator", db_path(dbname)),
+            body: &to_value(options)?,
+            idempotent: true,
         };
 
         api_request!(self, config)
     }
 
+    /// Streams every item of an EventLog or Feed, newest first, making
+    /// GET requests to `self.base_url/db/:dbname/iterator` for one page
+    /// of `page_size` items at a time
+    ///
+    /// Pages are requested as the stream is consumed, each bounded by the
+    /// hash of the oldest item of the previous page. The items are the log
+    /// entries, `{ hash, payload: { op, value }, ... }`, which the `stream`
+    /// of the `eventlog` and `feed` handles decode down to their value
+    pub fn stream_log<'a, T: DeserializeOwned + 'a>(
+        &'a self,
+        dbname: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<T, Error>> + 'a {
+        iterator::stream_log(self, dbname, page_size)
+    }
+
     /// Makes a GET request to `self.base_url/db/:dbname/value`,
     /// returning the database information on success
-    pub async fn get_db_index(&self, dbname: &str) -> Result<Value, Exception> {
+    pub async fn get_db_index(&self, dbname: &str) -> Result<Value, Error> {
         let config = RequestConfig {
+            operation: "get_db_index",
+            dbname: Some(dbname),
             rtype: RequestType::Get,
-            path: format!("db/{}/index", &dbname),
+            path: format!("{}/index", db_path(dbname)),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
@@ -145,19 +218,49 @@ impl Client {
 
     /// Makes a GET request to `self.base_url/identity`,
     /// returning the identity structure on success
-    pub async fn get_identity(&self) -> Result<Identity, Exception> {
+    pub async fn get_identity(&self) -> Result<Identity, Error> {
         let config = RequestConfig {
+            operation: "get_identity",
+            dbname: None,
             rtype: RequestType::Get,
             path: "identity".into(),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
     }
 
+    /// Makes a GET request to `self.base_url/db/:dbname/events/:events`,
+    /// returning a stream of the database's events of the given kinds
+    ///
+    /// The stream reconnects whenever the connection is lost, resuming
+    /// from the last received event, and ends once the server rejects
+    /// the subscription. Fails with `Error::InvalidQuery` if `events` is empty
+    pub fn subscribe(
+        &self,
+        dbname: &str,
+        events: &[EventKind],
+    ) -> Result<impl Stream<Item = Result<Event, Error>>, Error> {
+        if events.is_empty() {
+            return Err(Error::InvalidQuery(String::from(
+                "at least one event kind must be subscribed to",
+            )));
+        }
+        let events: Vec<String> = events.iter().map(EventKind::to_string).collect();
+        let uri =
+            self.base_url
+                .join(&format!("{}/events/{}", db_path(dbname), events.join(",")))?;
+
+        Ok(events::subscribe(self.sender.clone(), uri))
+    }
+
     /// Makes a POST request to `self.base_url/db/:dbname`,
     /// sending the specified db type and returning the
     /// database structure on success
+    ///
+    /// As the request may create or overwrite the database, it is only
+    /// retried by a `RetryPolicy` allowing non idempotent retries
     //
     // TODO: The use of the api arguments might be able to be done better
     // And there may be additional parameters that should be added
@@ -167,10 +270,12 @@ impl Client {
         dbtype: DatabaseType,
         ac: Option<AccessController>,
         overwrite: bool,
-    ) -> Result<Database, Exception> {
+    ) -> Result<Database, Error> {
         let config = RequestConfig {
+            operation: "create_db",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
-            path: format!("db/{}", dbname),
+            path: db_path(dbname),
             body: &json!({
                 "create": true,
                 "type": dbtype.to_string(),
@@ -181,6 +286,29 @@ impl Client {
                 "accessController": to_value(ac)?,
                 "overwrite": to_value(overwrite)?,
             }),
+            idempotent: false,
+        };
+
+        api_request!(self, config)
+    }
+
+    /// Makes a POST request to `self.base_url/db/:dbname`,
+    /// opening the database with the given options and returning
+    /// the database structure on success
+    ///
+    /// `dbname` may be the string of a full `OrbitDbAddress`, `address.as_ref()`,
+    /// so that a remote database is opened and replicated without being created.
+    ///
+    /// As the request may create the database, it is only retried by a
+    /// `RetryPolicy` allowing non idempotent retries
+    pub async fn open_db(&self, dbname: &str, options: OpenOptions) -> Result<Database, Error> {
+        let config = RequestConfig {
+            operation: "open_db",
+            dbname: Some(dbname),
+            rtype: RequestType::Post,
+            path: db_path(dbname),
+            body: &to_value(options)?,
+            idempotent: false,
         };
 
         api_request!(self, config)
@@ -189,11 +317,23 @@ impl Client {
     /// Makes a POST request to `self.base_url/db/:dbname/query`,
     /// sending the query to be interpretted and processed returning
     /// the items on success
-    pub async fn db_query(&self, dbname: &str, query: Query) -> Result<Vec<Value>, Exception> {
+    ///
+    /// Queries whose values do not fit their comparison are rejected
+    /// with `Error::InvalidQuery` before any request is made
+    pub async fn db_query<T: DeserializeOwned>(
+        &self,
+        dbname: &str,
+        query: Query,
+    ) -> Result<Vec<T>, Error> {
+        query.validate()?;
+
         let config = RequestConfig {
+            operation: "db_query",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
-            path: format!("db/{}/query", dbname),
+            path: format!("{}/query", db_path(dbname)),
             body: &to_value(query)?,
+            idempotent: true,
         };
 
         api_request!(self, config)
@@ -202,11 +342,21 @@ impl Client {
     /// Makes a POST request to `self.base_url/db/:dbname/add`,
     /// sending the entry to be added to the EventLog or Feed and returning
     /// the hash on success
-    pub async fn db_add(&self, dbname: &str, entry: &str) -> Result<Hash, Exception> {
+    ///
+    /// As the request appends an entry, it is only retried by a
+    /// `RetryPolicy` allowing non idempotent retries
+    pub async fn db_add<T: Serialize + ?Sized>(
+        &self,
+        dbname: &str,
+        entry: &T,
+    ) -> Result<Hash, Error> {
         let config = RequestConfig {
+            operation: "db_add",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
-            path: format!("db/{}/add", dbname),
+            path: format!("{}/add", db_path(dbname)),
             body: &to_value(entry)?,
+            idempotent: false,
         };
 
         api_request!(self, config)
@@ -215,11 +365,21 @@ impl Client {
     /// Makes a POST request to `self.base_url/db/:dbname/put`,
     /// sending the record to be added to the database and returning
     /// the hash on success
-    pub async fn db_put(&self, dbname: &str, record: &Value) -> Result<Hash, Exception> {
+    ///
+    /// As the request appends an entry, it is only retried by a
+    /// `RetryPolicy` allowing non idempotent retries
+    pub async fn db_put<T: Serialize + ?Sized>(
+        &self,
+        dbname: &str,
+        record: &T,
+    ) -> Result<Hash, Error> {
         let config = RequestConfig {
+            operation: "db_put",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
-            path: format!("db/{}/put", dbname),
-            body: record,
+            path: format!("{}/put", db_path(dbname)),
+            body: &to_value(record)?,
+            idempotent: false,
         };
 
         api_request!(self, config)
@@ -228,18 +388,24 @@ impl Client {
     /// Makes a POST request to `self.base_url/db/:dbname/inc`,
     /// to increment the counter database by 1 and returning
     /// the hash on success
+    ///
+    /// As the request appends an entry, it is only retried by a
+    /// `RetryPolicy` allowing non idempotent retries
     pub async fn inc_counter_value(
         &self,
         dbname: &str,
         amount: Option<u64>,
-    ) -> Result<Hash, Exception> {
+    ) -> Result<Hash, Error> {
         let config = RequestConfig {
+            operation: "inc_counter_value",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
             path: match amount {
-                Some(amount) => format!("db/{}/inc/{}", dbname, amount),
-                None => format!("db/{}/inc", dbname),
+                Some(amount) => format!("{}/inc/{}", db_path(dbname), amount),
+                None => format!("{}/inc", db_path(dbname)),
             },
             body: &Value::Null,
+            idempotent: false,
         };
 
         api_request!(self, config)
@@ -248,39 +414,77 @@ impl Client {
     /// Makes a POST request to `self.base_url/db/:dbname/access/write`,
     /// to add the id to the list of peers who have writing access
     /// for that database, returning the hash on success
-    pub async fn grant_write_access(&self, dbname: &str, id: String) -> Result<Hash, Exception> {
+    ///
+    /// As the request appends an entry to the access controller, it is only
+    /// retried by a `RetryPolicy` allowing non idempotent retries
+    pub async fn grant_write_access(&self, dbname: &str, id: String) -> Result<Hash, Error> {
         let config = RequestConfig {
+            operation: "grant_write_access",
+            dbname: Some(dbname),
             rtype: RequestType::Post,
-            path: format!("db/{}/access/write", dbname),
+            path: format!("{}/access/write", db_path(dbname)),
             body: &json!({ "id": id }),
+            idempotent: false,
         };
 
         api_request!(self, config)
     }
 
     /// Makes a DELETE request to `self.base_url/db/:dbname`,
-    /// to delete the specified database and returning
-    /// an empty hashmap on success
-    pub async fn delete_db(&self, dbname: &str) -> Result<HashMap<(), ()>, Exception> {
+    /// to delete the specified database
+    pub async fn delete_db(&self, dbname: &str) -> Result<(), Error> {
         let config = RequestConfig {
+            operation: "delete_db",
+            dbname: Some(dbname),
             rtype: RequestType::Delete,
-            path: format!("db/{}", dbname),
+            path: db_path(dbname),
             body: &Value::Null,
+            idempotent: true,
         };
 
-        api_request!(self, config)
+        // The server answers with an empty object or no content at all
+        let response: Result<Value, Error> = api_request!(self, config);
+        response.map(|_| ())
     }
 
     /// Makes a DELETE request to `self.base_url/db/:dbname/:item`,
     /// to delete the specified item from the database and returning
     /// the hash on success
-    pub async fn delete_db_item(&self, dbname: &str, item: &str) -> Result<Hash, Exception> {
+    pub async fn delete_db_item(&self, dbname: &str, item: &str) -> Result<Hash, Error> {
         let config = RequestConfig {
+            operation: "delete_db_item",
+            dbname: Some(dbname),
             rtype: RequestType::Delete,
-            path: format!("db/{}/{}", dbname, item),
+            path: item_path(dbname, item),
             body: &Value::Null,
+            idempotent: true,
         };
 
         api_request!(self, config)
     }
+
+    /// Makes a request of type `rtype` to `self.base_url/:path`, sending the
+    /// `body` json value, if any, and returning the decoded response along
+    /// with its status
+    ///
+    /// This reaches the endpoints without a method of their own, and tells
+    /// apart successful statuses such as `200 OK` and `204 No Content`.
+    /// POST requests are not retried, as they may append to a database
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        rtype: RequestType,
+        path: &str,
+        body: &Value,
+    ) -> Result<ApiResponse<T>, Error> {
+        let config = RequestConfig {
+            operation: "request",
+            dbname: None,
+            rtype,
+            path: path.into(),
+            body,
+            idempotent: rtype != RequestType::Post,
+        };
+
+        api_request!(@response self, config)
+    }
 }
@@ -0,0 +1,202 @@
+//! The metadata returned by the REST API about databases, entries and identities
+
+use super::*;
+use serde::Deserialize;
+
+/// The information pertaining to an OrbitDB database
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Database {
+    /// The address of the database
+    pub address: Address,
+    /// The name of the database
+    pub dbname: String,
+    /// The address of the database, as a string
+    pub id: String,
+    /// The options the database was opened with
+    pub options: Options,
+    /// Whether the server's identity may write to the database
+    pub can_append: bool,
+    /// The ids of the identities allowed to write to the database, `*` allowing anyone
+    pub write: Vec<String>,
+    /// The type of the database
+    ///
+    /// The index of a DocStore is found in `options.index_by`, so its
+    /// `index_by` is left unset
+    pub r#type: DatabaseType,
+    /// The operations the database supports
+    pub capabilities: Vec<Capability>,
+}
+
+/// The address of an OrbitDB database, `/orbitdb/:root/:path`, as returned
+/// by the server
+///
+/// Converts into a validated `OrbitDbAddress` with `OrbitDbAddress::try_from`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Address {
+    /// The hash of the database manifest
+    pub root: String,
+    /// The name of the database
+    pub path: String,
+}
+
+/// The options an OrbitDB database was opened with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Options {
+    /// Whether the database was created if it did not exist
+    pub create: bool,
+    /// The property documents are indexed by, for a DocStore
+    pub index_by: Option<String>,
+    /// Whether the database was only opened if stored locally
+    pub local_only: bool,
+    /// The number of entries loaded when opening the database, -1 loading all of them
+    pub max_history: i64,
+    /// Whether an existing database was overwritten
+    pub overwrite: bool,
+    /// Whether the database is replicated with peers
+    pub replicate: bool,
+}
+
+/// Options for opening a database with `Client::open_db`, the server's
+/// defaults applying to those not specified
+///
+/// Unless `create` is set, only an existing database is opened, which may
+/// be a remote database replicated from its full OrbitDB address
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenOptions {
+    /// Whether to create the database if it does not exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub create: Option<bool>,
+    /// The type of the database, required to create it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<DatabaseType>,
+    /// The property documents are indexed by, when creating a DocStore
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index_by: Option<String>,
+    /// Whether to only open the database if it is stored locally
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_only: Option<bool>,
+    /// Whether to replicate the database with peers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replicate: Option<bool>,
+    /// The number of entries to load, -1 loading all of them
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_history: Option<i64>,
+    /// The directory the server stores the database in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+    /// Whether to wait for the database to sync with its peers before returning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sync: Option<bool>,
+}
+
+impl OpenOptions {
+    /// Options opening an existing database with the server's defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the database if it does not exist when `create` is true
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = Some(create);
+        self
+    }
+
+    /// Opens the database as a `dbtype`, which is required to create it,
+    /// along with the index of a DocStore
+    pub fn dbtype(mut self, dbtype: DatabaseType) -> Self {
+        if let DatabaseType::DocStore { index_by } = &dbtype {
+            self.index_by = index_by.clone();
+        }
+        self.r#type = Some(dbtype);
+        self
+    }
+
+    /// Only opens the database if it is stored locally when `local_only` is true
+    pub fn local_only(mut self, local_only: bool) -> Self {
+        self.local_only = Some(local_only);
+        self
+    }
+
+    /// Replicates the database with peers when `replicate` is true
+    pub fn replicate(mut self, replicate: bool) -> Self {
+        self.replicate = Some(replicate);
+        self
+    }
+
+    /// Loads at most `max_history` entries, or all of them if `max_history` is -1
+    pub fn max_history(mut self, max_history: i64) -> Self {
+        self.max_history = Some(max_history);
+        self
+    }
+
+    /// Stores the database in `directory` on the server
+    pub fn directory(mut self, directory: &str) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// Waits for the database to sync with its peers when `sync` is true
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = Some(sync);
+        self
+    }
+}
+
+/// The operations a database supports, depending on its type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// Adding entries, with `Client::db_add`
+    Add,
+    /// Getting items, with `Client::get_db_item`
+    Get,
+    /// Incrementing a counter, with `Client::inc_counter_value`
+    Inc,
+    /// Iterating over entries, with `Client::get_db_iterator`
+    Iterator,
+    /// Putting records, with `Client::db_put`
+    Put,
+    /// Querying documents, with `Client::db_query`
+    Query,
+    /// Removing items, with `Client::delete_db_item`
+    Remove,
+    /// Getting a counter's value, with `Client::get_counter_value`
+    Value,
+    /// A capability unknown to this client
+    #[serde(other)]
+    Other,
+}
+
+/// The hash of an entry, identifying it in its database's log
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Hash {
+    /// The hash, a CID
+    pub hash: String,
+}
+
+/// The information to uniquely identify the OrbitDB instance and sign its entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Identity {
+    /// The id of the identity
+    pub id: String,
+    /// The public key signing the entries
+    pub public_key: String,
+    /// The signatures binding the id to the public key
+    pub signatures: Signatures,
+    /// The type of the identity provider, e.g. `orbitdb`
+    pub r#type: String,
+}
+
+/// The signatures of an `Identity`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Signatures {
+    /// The signature of the id
+    pub id: String,
+    /// The signature of the public key
+    pub public_key: String,
+}
@@ -0,0 +1,194 @@
+use http::StatusCode;
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+/// The errors that can occur when making requests to an OrbitDB REST API
+#[derive(Debug)]
+pub enum Error {
+    /// The HTTP request could not be sent or its response could not be read
+    Transport(Box<dyn std::error::Error + Send + Sync>),
+    /// The connection to the server could not be established or was lost,
+    /// e.g. refused or reset, before the response was read
+    Connection(Box<dyn std::error::Error + Send + Sync>),
+    /// The request url could not be built from the client's base url
+    Url(url::ParseError),
+    /// A request body could not be encoded or a response body could not be decoded
+    Decode(serde_json::Error),
+    /// The server responded with an error envelope
+    Server(ErrorResponse),
+    /// The requested database or item does not exist
+    NotFound(ErrorResponse),
+    /// The query's values do not fit its comparison
+    InvalidQuery(String),
+    /// The string is not a valid OrbitDB address
+    InvalidAddress(String),
+    /// The request did not complete within the configured timeout
+    Timeout(Duration),
+    /// The client's settings cannot be used together, e.g. TLS settings
+    /// given for a transport they cannot be applied to
+    Config(String),
+}
+
+/// The error envelope returned by the OrbitDB REST API
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorResponse {
+    /// The HTTP status of the response, or the `statusCode` reported in the body
+    pub status_code: Option<u16>,
+    /// The short error name, e.g. `Not Found`
+    pub error: Option<String>,
+    /// The message describing the error
+    pub message: String,
+    /// Any additional `details` sent along with the error
+    pub details: Option<Value>,
+    /// The full response body
+    pub raw: Value,
+}
+
+impl ErrorResponse {
+    /// Parses an error envelope, tolerating missing or unexpectedly typed fields
+    pub fn from_value(raw: Value) -> Self {
+        let status_code = match &raw["statusCode"] {
+            Value::Number(status) => status.as_u64(),
+            Value::String(status) => status.parse().ok(),
+            _ => None,
+        }
+        .and_then(|status| u16::try_from(status).ok());
+        let error = text(&raw["error"]);
+        let message = text(&raw["message"])
+            .or_else(|| error.clone())
+            .unwrap_or_else(|| String::from("unknown error"));
+        let details = raw.get("details").filter(|d| !d.is_null()).cloned();
+
+        ErrorResponse {
+            status_code,
+            error,
+            message,
+            details,
+            raw,
+        }
+    }
+}
+
+/// Renders a json field as text, keeping strings unquoted
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(text.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+impl Error {
+    /// Builds the error corresponding to an error status or OrbitDB error envelope
+    pub(crate) fn from_response(status: u16, body: Value) -> Self {
+        let mut response = ErrorResponse::from_value(body);
+
+        if status >= 400 {
+            response.status_code = Some(status);
+            if response.error.is_none() {
+                response.error = StatusCode::from_u16(status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .map(String::from);
+            }
+            // Bodies that are not an envelope carry no message field of their own
+            if text(&response.raw["message"]).is_none() {
+                response.message = match &response.raw {
+                    Value::String(text) if !text.trim().is_empty() => text.clone(),
+                    _ => response.error.clone().unwrap_or_else(|| status.to_string()),
+                };
+            }
+        }
+
+        match response.status_code {
+            Some(404) => Error::NotFound(response),
+            _ => Error::Server(response),
+        }
+    }
+
+    /// A short name for the kind of error, e.g. `timeout` or `not_found`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Transport(_) => "transport",
+            Error::Connection(_) => "connection",
+            Error::Url(_) => "url",
+            Error::Decode(_) => "decode",
+            Error::Server(_) => "server",
+            Error::NotFound(_) => "not_found",
+            Error::InvalidQuery(_) => "invalid_query",
+            Error::InvalidAddress(_) => "invalid_address",
+            Error::Timeout(_) => "timeout",
+            Error::Config(_) => "config",
+        }
+    }
+
+    /// The status code of a server error, if any
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::Server(response) | Error::NotFound(response) => response.status_code,
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transport(err) => write!(f, "transport error: {}", err),
+            Error::Connection(err) => write!(f, "connection error: {}", err),
+            Error::Url(err) => write!(f, "invalid request url: {}", err),
+            Error::Decode(err) => write!(f, "invalid json: {}", err),
+            Error::Server(response) => match response.status_code {
+                Some(status) => write!(f, "server error ({}): {}", status, response.message),
+                None => write!(f, "server error: {}", response.message),
+            },
+            Error::NotFound(response) => write!(f, "not found: {}", response.message),
+            Error::InvalidQuery(reason) => write!(f, "invalid query: {}", reason),
+            Error::InvalidAddress(reason) => write!(f, "invalid OrbitDB address: {}", reason),
+            Error::Timeout(timeout) => write!(f, "request timed out after {:?}", timeout),
+            Error::Config(reason) => write!(f, "invalid client configuration: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Transport(err) | Error::Connection(err) => Some(err.as_ref()),
+            Error::Url(err) => Some(err),
+            Error::Decode(err) => Some(err),
+            Error::Server(_)
+            | Error::NotFound(_)
+            | Error::InvalidQuery(_)
+            | Error::InvalidAddress(_)
+            | Error::Timeout(_)
+            | Error::Config(_) => None,
+        }
+    }
+}
+
+impl From<Box<dyn std::error::Error + Send + Sync>> for Error {
+    fn from(err: Box<dyn std::error::Error + Send + Sync>) -> Self {
+        Error::Transport(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Transport(Box::new(err))
+    }
+}
+
+impl From<url::ParseError> for Error {
+    fn from(err: url::ParseError) -> Self {
+        Error::Url(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Decode(err)
+    }
+}
@@ -0,0 +1,273 @@
+//! Subscriptions to the server-sent events of a database
+
+use super::*;
+use futures::stream::{self, Stream, StreamExt};
+use sender::Sender;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::Duration;
+use transport::{BodyStream, Request};
+use url::Url;
+
+/// How long to wait before reconnecting when the server has not sent a `retry` field
+const DEFAULT_RETRY: Duration = Duration::from_secs(3);
+
+/// The events emitted by an OrbitDB database
+#[derive(Debug, Clone, Copy, PartialEq, ToString, EnumString)]
+pub enum EventKind {
+    /// The database was replicated from a peer
+    #[strum(serialize = "replicated")]
+    Replicated,
+    /// Replication from a peer started
+    #[strum(serialize = "replicate")]
+    Replicate,
+    /// An entry was received while replicating
+    #[strum(serialize = "replicate.progress")]
+    ReplicateProgress,
+    /// The database started loading from disk
+    #[strum(serialize = "load")]
+    Load,
+    /// An entry was loaded from disk
+    #[strum(serialize = "load.progress")]
+    LoadProgress,
+    /// The database finished loading
+    #[strum(serialize = "ready")]
+    Ready,
+    /// An entry was written locally
+    #[strum(serialize = "write")]
+    Write,
+    /// The database was closed
+    #[strum(serialize = "closed")]
+    Closed,
+    /// A peer subscribed to the database
+    #[strum(serialize = "peer")]
+    Peer,
+}
+
+/// An event received from a database subscription
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// The id of the event, sent back as `Last-Event-ID` when reconnecting
+    pub id: Option<String>,
+    /// The kind of the event
+    pub kind: EventKind,
+    /// The json payload of the event, or the raw text if it is not json
+    pub data: Value,
+}
+
+/// A message decoded from a `text/event-stream` body
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct Message {
+    pub(crate) id: Option<String>,
+    pub(crate) event: Option<String>,
+    pub(crate) data: String,
+}
+
+/// Incrementally decodes a `text/event-stream` body into `Message`s
+#[derive(Debug, Default)]
+pub(crate) struct Decoder {
+    /// Bytes of an incomplete line
+    line: Vec<u8>,
+    /// Whether the last chunk ended with `\r`, whose `\n` may start the next one
+    after_cr: bool,
+    /// The message being decoded
+    message: Message,
+    /// Whether the message has any data lines
+    has_data: bool,
+    /// The reconnection delay most recently requested by the server
+    pub(crate) retry: Option<Duration>,
+}
+
+impl Decoder {
+    /// Decodes a chunk of the body, returning the messages it completes
+    pub(crate) fn decode(&mut self, chunk: &[u8]) -> Vec<Message> {
+        let mut messages = vec![];
+
+        for &byte in chunk {
+            let after_cr = std::mem::replace(&mut self.after_cr, byte == b'\r');
+
+            match byte {
+                b'\n' if after_cr => (),
+                b'\r' | b'\n' => {
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(message) = self.line(&String::from_utf8_lossy(&line)) {
+                        messages.push(message);
+                    }
+                }
+                _ => self.line.push(byte),
+            }
+        }
+        messages
+    }
+
+    /// Handles a complete line, returning the message dispatched by a blank line
+    fn line(&mut self, line: &str) -> Option<Message> {
+        if line.is_empty() {
+            let message = std::mem::take(&mut self.message);
+            let has_data = std::mem::replace(&mut self.has_data, false);
+
+            // The last event id carries over to the following messages
+            self.message.id = message.id.clone();
+            return if has_data { Some(message) } else { None };
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.find(':') {
+            Some(i) => (&line[..i], &line[i + 1..]),
+            None => (line, ""),
+        };
+        let value = value.strip_prefix(' ').unwrap_or(value);
+
+        match field {
+            "event" => self.message.event = Some(value.into()),
+            "data" => {
+                if self.has_data {
+                    self.message.data.push('\n');
+                }
+                self.message.data.push_str(value);
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.message.id = Some(value.into()),
+            "retry" => {
+                if let Ok(millis) = value.parse() {
+                    self.retry = Some(Duration::from_millis(millis));
+                }
+            }
+            _ => (),
+        }
+        None
+    }
+}
+
+/// The state of a subscription between polls of its stream
+struct Subscription {
+    sender: Arc<Sender>,
+    uri: Url,
+    body: Option<BodyStream>,
+    decoder: Decoder,
+    pending: VecDeque<Event>,
+    last_event_id: Option<String>,
+    retry: Duration,
+    /// Whether the connection has been lost and must be re-established after `retry`
+    reconnecting: bool,
+    done: bool,
+}
+
+impl Subscription {
+    /// Opens the event stream, resuming from the last received event
+    async fn connect(&mut self) -> Result<(), Error> {
+        let mut request = Request {
+            method: RequestType::Get,
+            url: self.uri.clone(),
+            headers: vec![("Accept".into(), "text/event-stream".into())],
+            body: None,
+        };
+        if let Some(id) = &self.last_event_id {
+            request.headers.push(("Last-Event-ID".into(), id.clone()));
+        }
+
+        let response = self.sender.send_streaming(request).await?;
+        if response.status >= 400 {
+            // Only the error body is of interest here, the stream is not resumed
+            self.done = true;
+            let response = response.into_response().await?;
+            return decode_response(response.status, &response.body).map(|_| ());
+        }
+
+        self.decoder = Decoder::default();
+        self.body = Some(response.body);
+        Ok(())
+    }
+
+    /// Reads the next chunk of the open stream, queueing the events it completes
+    async fn read(&mut self) -> Result<(), Error> {
+        let chunk = match &mut self.body {
+            Some(body) => body.next().await,
+            None => return Ok(()),
+        };
+
+        match chunk {
+            Some(Ok(chunk)) => {
+                for message in self.decoder.decode(&chunk) {
+                    self.receive(message);
+                }
+                if let Some(retry) = self.decoder.retry {
+                    self.retry = retry;
+                }
+                Ok(())
+            }
+            end => {
+                self.body = None;
+                self.reconnecting = true;
+                end.unwrap_or(Ok(vec![])).map(|_| ())
+            }
+        }
+    }
+
+    /// Records a decoded message, queueing it if it is a database event
+    fn receive(&mut self, message: Message) {
+        if message.id.is_some() {
+            self.last_event_id = message.id.clone();
+        }
+
+        // Messages such as the server's acknowledgement of the subscription are skipped
+        let kind = match message.event.as_deref().unwrap_or("message").parse() {
+            Ok(kind) => kind,
+            Err(_) => return,
+        };
+        let data = serde_json::from_str(&message.data).unwrap_or(Value::String(message.data));
+
+        self.pending.push_back(Event {
+            id: message.id,
+            kind,
+            data,
+        });
+    }
+
+    /// Gets the next event, connecting and reconnecting as needed
+    async fn next(&mut self) -> Option<Result<Event, Error>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return None;
+            }
+
+            let result = if self.body.is_some() {
+                self.read().await
+            } else {
+                if self.reconnecting {
+                    runtime::sleep(self.retry).await;
+                }
+                self.reconnecting = true;
+                self.connect().await
+            };
+            if let Err(err) = result {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+/// Creates the stream of events of the database at `uri`
+pub(crate) fn subscribe(sender: Arc<Sender>, uri: Url) -> impl Stream<Item = Result<Event, Error>> {
+    let subscription = Subscription {
+        sender,
+        uri,
+        body: None,
+        decoder: Decoder::default(),
+        pending: VecDeque::new(),
+        last_event_id: None,
+        retry: DEFAULT_RETRY,
+        reconnecting: false,
+        done: false,
+    };
+
+    stream::unfold(subscription, |mut subscription| async move {
+        let next = subscription.next().await?;
+        Some((next, subscription))
+    })
+}
@@ -0,0 +1,238 @@
+//! Typed handles restricting a database to the operations valid for its `DatabaseType`
+
+use super::*;
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::json;
+use std::marker::PhantomData;
+
+/// Decodes the values added to a log from its entries,
+/// `{ hash, payload: { op, value }, next, clock, ... }`
+fn entry_values<T: DeserializeOwned>(entries: Vec<Value>) -> Result<Vec<T>, Error> {
+    entries.into_iter().map(entry_value).collect()
+}
+
+/// Decodes the value added to a log from its entry
+fn entry_value<T: DeserializeOwned>(mut entry: Value) -> Result<T, Error> {
+    Ok(serde_json::from_value(entry["payload"]["value"].take())?)
+}
+
+/// A handle to an EventLog database whose entries are of type `T`
+pub struct EventLog<'a, T> {
+    client: &'a Client,
+    dbname: String,
+    entry: PhantomData<T>,
+}
+
+impl<'a, T> EventLog<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        EventLog {
+            client,
+            dbname: dbname.into(),
+            entry: PhantomData,
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        &self.dbname
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> EventLog<'a, T> {
+    /// Adds an entry to the log, returning its hash
+    pub async fn add(&self, entry: &T) -> Result<Hash, Error> {
+        self.client.db_add(&self.dbname, entry).await
+    }
+
+    /// Gets the entry identified by `hash`
+    pub async fn get(&self, hash: &str) -> Result<Vec<T>, Error> {
+        entry_values(self.client.get_db_item(&self.dbname, hash).await?)
+    }
+
+    /// Gets the entries of the log selected by `options`
+    pub async fn iterator(&self, options: impl Into<IteratorOptions>) -> Result<Vec<T>, Error> {
+        entry_values(self.client.get_db_iterator(&self.dbname, options).await?)
+    }
+
+    /// Streams every entry of the log, newest first, `page_size` entries at a time
+    pub fn stream(&self, page_size: usize) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        self.client
+            .stream_log(&self.dbname, page_size)
+            .map(|entry| entry.and_then(entry_value))
+    }
+}
+
+/// A handle to a Feed database whose entries are of type `T`
+pub struct Feed<'a, T> {
+    client: &'a Client,
+    dbname: String,
+    entry: PhantomData<T>,
+}
+
+impl<'a, T> Feed<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        Feed {
+            client,
+            dbname: dbname.into(),
+            entry: PhantomData,
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        &self.dbname
+    }
+
+    /// Removes the entry identified by `hash`, returning the hash of the removal
+    pub async fn remove(&self, hash: &str) -> Result<Hash, Error> {
+        self.client.delete_db_item(&self.dbname, hash).await
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> Feed<'a, T> {
+    /// Adds an entry to the feed, returning its hash
+    pub async fn add(&self, entry: &T) -> Result<Hash, Error> {
+        self.client.db_add(&self.dbname, entry).await
+    }
+
+    /// Gets the entry identified by `hash`
+    pub async fn get(&self, hash: &str) -> Result<Vec<T>, Error> {
+        entry_values(self.client.get_db_item(&self.dbname, hash).await?)
+    }
+
+    /// Gets the entries of the feed selected by `options`
+    pub async fn iterator(&self, options: impl Into<IteratorOptions>) -> Result<Vec<T>, Error> {
+        entry_values(self.client.get_db_iterator(&self.dbname, options).await?)
+    }
+
+    /// Streams every entry of the feed, newest first, `page_size` entries at a time
+    pub fn stream(&self, page_size: usize) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        self.client
+            .stream_log(&self.dbname, page_size)
+            .map(|entry| entry.and_then(entry_value))
+    }
+}
+
+/// A handle to a DocStore database whose documents are of type `T`
+pub struct DocStore<'a, T> {
+    client: &'a Client,
+    dbname: String,
+    document: PhantomData<T>,
+}
+
+impl<'a, T> DocStore<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        DocStore {
+            client,
+            dbname: dbname.into(),
+            document: PhantomData,
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        &self.dbname
+    }
+
+    /// Deletes the document indexed by `key`, returning the hash of the deletion
+    pub async fn delete(&self, key: &str) -> Result<Hash, Error> {
+        self.client.delete_db_item(&self.dbname, key).await
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> DocStore<'a, T> {
+    /// Puts a document into the store, returning its hash
+    pub async fn put(&self, document: &T) -> Result<Hash, Error> {
+        self.client.db_put(&self.dbname, document).await
+    }
+
+    /// Gets the documents indexed by `key`
+    pub async fn get(&self, key: &str) -> Result<Vec<T>, Error> {
+        self.client.get_db_item(&self.dbname, key).await
+    }
+
+    /// Gets the documents matching `query`
+    pub async fn query(&self, query: Query) -> Result<Vec<T>, Error> {
+        self.client.db_query(&self.dbname, query).await
+    }
+}
+
+/// A handle to a KeyValue database whose values are of type `T`
+pub struct KeyValue<'a, T> {
+    client: &'a Client,
+    dbname: String,
+    value: PhantomData<T>,
+}
+
+impl<'a, T> KeyValue<'a, T> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        KeyValue {
+            client,
+            dbname: dbname.into(),
+            value: PhantomData,
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        &self.dbname
+    }
+
+    /// Deletes the value stored under `key`, returning the hash of the deletion
+    pub async fn delete(&self, key: &str) -> Result<Hash, Error> {
+        self.client.delete_db_item(&self.dbname, key).await
+    }
+}
+
+impl<'a, T: Serialize + DeserializeOwned> KeyValue<'a, T> {
+    /// Stores `value` under `key`, returning the hash of the entry
+    pub async fn put(&self, key: &str, value: &T) -> Result<Hash, Error> {
+        let record = json!({ "key": key, "value": value });
+
+        self.client.db_put(&self.dbname, &record).await
+    }
+
+    /// Gets the value stored under `key`, if any
+    pub async fn get(&self, key: &str) -> Result<Option<T>, Error> {
+        let values: Vec<T> = self.client.get_db_item(&self.dbname, key).await?;
+
+        Ok(values.into_iter().next())
+    }
+}
+
+/// A handle to a Counter database
+pub struct Counter<'a> {
+    client: &'a Client,
+    dbname: String,
+}
+
+impl<'a> Counter<'a> {
+    pub(crate) fn new(client: &'a Client, dbname: &str) -> Self {
+        Counter {
+            client,
+            dbname: dbname.into(),
+        }
+    }
+
+    /// The name of the database
+    pub fn dbname(&self) -> &str {
+        &self.dbname
+    }
+
+    /// Gets the counter's value
+    pub async fn value(&self) -> Result<u64, Error> {
+        self.client.get_counter_value(&self.dbname).await
+    }
+
+    /// Increments the counter by `amount` (1 if not specified), returning the hash
+    pub async fn inc(&self, amount: Option<u64>) -> Result<Hash, Error> {
+        self.client.inc_counter_value(&self.dbname, amount).await
+    }
+}
@@ -0,0 +1,98 @@
+//! Instrumentation of the requests made by `Client` methods
+//!
+//! With the `tracing` cargo feature, each request runs within an `orbitdb`
+//! span recording its operation, database, method, path, status, latency
+//! and error kind. With the `metrics` cargo feature, requests are counted
+//! in the client's `Metrics`, if any. Without either, requests are run as
+//! they are
+
+use super::*;
+use sender::Sender;
+use std::future::Future;
+use transport::Response;
+
+/// Runs the request made for `config` by `future`, through `sender`
+pub(crate) async fn request<T, F>(
+    sender: &Sender,
+    config: &RequestConfig<'_>,
+    future: F,
+) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    #[cfg(any(feature = "tracing", feature = "metrics"))]
+    let start = std::time::Instant::now();
+
+    #[cfg(feature = "tracing")]
+    let (span, result) = {
+        use tracing::field::Empty;
+        use tracing::Instrument;
+
+        let span = tracing::info_span!(
+            "orbitdb",
+            operation = config.operation,
+            dbname = config.dbname,
+            method = config.rtype.as_str(),
+            path = config.path.as_str(),
+            status = Empty,
+            latency_ms = Empty,
+            error = Empty,
+        );
+        let result = future.instrument(span.clone()).await;
+        (span, result)
+    };
+    #[cfg(not(feature = "tracing"))]
+    let result = future.await;
+
+    #[cfg(feature = "tracing")]
+    {
+        span.record("latency_ms", start.elapsed().as_millis() as u64);
+        match &result {
+            Ok(_) => tracing::debug!(parent: &span, "request succeeded"),
+            Err(err) => {
+                if let Some(status) = err.status() {
+                    span.record("status", status);
+                }
+                span.record("error", err.kind());
+                tracing::warn!(parent: &span, error = %err, "request failed");
+            }
+        }
+    }
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = &sender.metrics {
+        let failure = result.as_ref().err().map(Error::kind);
+        metrics.request(config.operation, start.elapsed(), failure);
+    }
+
+    let _ = (sender, config);
+    result
+}
+
+/// Records the `response` received for the request made for `config`,
+/// whose body was `sent` bytes long
+pub(crate) fn response(
+    sender: &Sender,
+    config: &RequestConfig<'_>,
+    sent: usize,
+    response: &Response,
+) {
+    #[cfg(feature = "tracing")]
+    tracing::Span::current().record("status", response.status);
+    #[cfg(feature = "metrics")]
+    if let Some(metrics) = &sender.metrics {
+        metrics.bytes(config.operation, sent, response.body.len());
+    }
+
+    let _ = (sender, config, sent, response);
+}
+
+/// Records that the failed attempt `attempt` of the current request is retried
+pub(crate) fn retry(attempt: u32, result: &Result<Response, Error>) {
+    #[cfg(feature = "tracing")]
+    match result {
+        Ok(response) => tracing::debug!(attempt, status = response.status, "retrying request"),
+        Err(err) => tracing::debug!(attempt, error = %err, "retrying request"),
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (attempt, result);
+}
@@ -0,0 +1,143 @@
+use super::*;
+use futures::stream::{self, Stream, StreamExt};
+use serde::de::{DeserializeOwned, Error as _};
+
+/// Options selecting the entries returned by `Client::get_db_iterator`
+///
+/// The `gt`, `gte`, `lt` and `lte` bounds are entry hashes, so a page
+/// of a log can be fetched by bounding it with the last hash of the
+/// previous page
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct IteratorOptions {
+    /// The maximum number of entries, all of them if not specified
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+    /// Only entries after the entry with this hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gt: Option<String>,
+    /// Only the entry with this hash and those after it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gte: Option<String>,
+    /// Only entries before the entry with this hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lt: Option<String>,
+    /// Only the entry with this hash and those before it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lte: Option<String>,
+    /// Whether to reverse the order of the returned entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverse: Option<bool>,
+}
+
+impl IteratorOptions {
+    /// Options returning every entry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns at most `limit` entries, or all of them if `limit` is -1
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Returns only entries after the entry with hash `hash`
+    pub fn gt(mut self, hash: &str) -> Self {
+        self.gt = Some(hash.into());
+        self
+    }
+
+    /// Returns only the entry with hash `hash` and those after it
+    pub fn gte(mut self, hash: &str) -> Self {
+        self.gte = Some(hash.into());
+        self
+    }
+
+    /// Returns only entries before the entry with hash `hash`
+    pub fn lt(mut self, hash: &str) -> Self {
+        self.lt = Some(hash.into());
+        self
+    }
+
+    /// Returns only the entry with hash `hash` and those before it
+    pub fn lte(mut self, hash: &str) -> Self {
+        self.lte = Some(hash.into());
+        self
+    }
+
+    /// Reverses the order of the returned entries when `reverse` is true
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = Some(reverse);
+        self
+    }
+}
+
+/// Options only limiting the number of entries, `None` selecting every entry
+impl From<Option<i64>> for IteratorOptions {
+    fn from(limit: Option<i64>) -> Self {
+        IteratorOptions {
+            limit,
+            ..Self::default()
+        }
+    }
+}
+
+/// Where the next page of a streamed log starts
+enum Cursor {
+    /// The newest entries have not been fetched yet
+    Newest,
+    /// The next page holds the entries before the entry with this hash
+    Before(String),
+    /// Every entry has been fetched
+    Done,
+}
+
+/// Streams the entries of the EventLog or Feed `dbname`, newest first,
+/// fetching `page_size` entries at a time
+///
+/// Each page of the iterator holds the entries preceding the oldest entry
+/// of the previous page, in chronological order
+pub(crate) fn stream_log<'a, T: DeserializeOwned + 'a>(
+    client: &'a Client,
+    dbname: &str,
+    page_size: usize,
+) -> impl Stream<Item = Result<T, Error>> + 'a {
+    let dbname = dbname.to_string();
+    let limit = page_size.max(1) as i64;
+
+    stream::unfold(Cursor::Newest, move |cursor| {
+        let dbname = dbname.clone();
+
+        async move {
+            let options = match cursor {
+                Cursor::Newest => IteratorOptions::new().limit(limit),
+                Cursor::Before(hash) => IteratorOptions::new().limit(limit).lt(&hash),
+                Cursor::Done => return None,
+            };
+
+            let page: Vec<Value> = match client.get_db_iterator(&dbname, options).await {
+                Ok(page) => page,
+                Err(err) => return Some((vec![Err(err)], Cursor::Done)),
+            };
+            let next = match page.first() {
+                _ if (page.len() as i64) < limit => Cursor::Done,
+                Some(oldest) => match oldest["hash"].as_str() {
+                    Some(hash) => Cursor::Before(hash.into()),
+                    None => {
+                        let err = serde_json::Error::custom("log entry has no hash");
+                        return Some((vec![Err(err.into())], Cursor::Done));
+                    }
+                },
+                None => Cursor::Done,
+            };
+            let entries = page
+                .into_iter()
+                .rev()
+                .map(|entry| serde_json::from_value(entry).map_err(Error::from))
+                .collect();
+
+            Some((entries, next))
+        }
+    })
+    .flat_map(stream::iter)
+}
@@ -3,65 +3,162 @@
 use serde::Serialize;
 use serde_json::Value;
 
+pub use address::OrbitDbAddress;
+pub use auth::Auth;
+pub use builder::ClientBuilder;
 pub use client::Client;
+pub use database::{
+    Address, Capability, Database, Hash, Identity, OpenOptions, Options, Signatures,
+};
+pub use error::{Error, ErrorResponse};
+pub use events::{Event, EventKind};
+pub use handles::{Counter, DocStore, EventLog, Feed, KeyValue};
+pub use iterator::IteratorOptions;
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+pub use middleware::Middleware;
+pub use query::{Comparison, Query, QueryBuilder};
+pub use retry::RetryPolicy;
+pub use transport::{TlsConfig, Transport};
 
 extern crate strum;
 #[macro_use]
 extern crate strum_macros;
 
 /// The types of API requests
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum RequestType {
     Get,
     Post,
     Delete,
 }
 
+impl RequestType {
+    /// The HTTP method name, e.g. `GET`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestType::Get => "GET",
+            RequestType::Post => "POST",
+            RequestType::Delete => "DELETE",
+        }
+    }
+}
+
 /// Settings for an API request
 struct RequestConfig<'a> {
+    /// The name of the `Client` method making the request
+    #[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(dead_code))]
+    operation: &'static str,
+    /// The database the request is about, if any
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    dbname: Option<&'a str>,
     /// The type of request
     rtype: RequestType,
     /// The path to be concatenated to the client's base url
     path: String,
     /// The body json value
     body: &'a Value,
+    /// Whether sending the request twice has the same effect as sending it
+    /// once, i.e. it does not append an entry to the database
+    idempotent: bool,
 }
 
-/// Makes an arbitrary API request based on the provided `Client` and `RequestConfig`
+/// A decoded API response, along with its HTTP status
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse<T> {
+    /// The HTTP status of the response, e.g. `200` or `204`
+    pub status: u16,
+    /// The decoded body of the response
+    pub value: T,
+}
+
+/// Makes an arbitrary API request based on the provided `Client` and `RequestConfig`,
+/// decoding the response body, along with its status with `@response`
 macro_rules! api_request {
-    ($client:ident, $config:ident) => {{
-        let uri = $client.base_url.join(&$config.path)?;
-
-        let response: Value = match $config.rtype {
-            RequestType::Get => {
-                surf::get(&uri)
-                    .body_json($config.body)?
-                    .await?
-                    .body_json()
-                    .await?
-            }
-            RequestType::Post => {
-                surf::post(&uri)
-                    .body_json($config.body)?
-                    .await?
-                    .body_json()
-                    .await?
+    (@response $client:ident, $config:ident) => {{
+        instrument::request(&$client.sender, &$config, async {
+            let mut request = transport::Request {
+                method: $config.rtype,
+                url: $client.base_url.join(&$config.path)?,
+                headers: vec![],
+                body: None,
+            };
+            if let RequestType::Get | RequestType::Post = $config.rtype {
+                request
+                    .headers
+                    .push(("Content-Type".into(), "application/json".into()));
+                request.body = Some(serde_json::to_vec($config.body)?);
             }
-            RequestType::Delete => surf::delete(&uri).recv_json().await?,
-        };
 
-        if response.get("error").is_some() {
-            Err(response["message"].as_str().unwrap())?
-        }
+            let sent = request.body.as_ref().map_or(0, Vec::len);
+            let response = $client.sender.send(request, $config.idempotent).await?;
+            instrument::response(&$client.sender, &$config, sent, &response);
+            let value = decode_response(response.status, &response.body)?;
 
-        let value = serde_json::from_value(response)?;
-        Ok(value)
+            Ok(ApiResponse {
+                status: response.status,
+                value: serde_json::from_value(value)?,
+            })
+        })
+        .await
     }};
+    ($client:ident, $config:ident) => {
+        api_request!(@response $client, $config).map(|response: ApiResponse<_>| response.value)
+    };
+}
+
+/// Decodes the body of an API response, mapping error statuses and their
+/// error envelopes into `Error`s
+///
+/// The bodies of successful responses are returned as they are, even when
+/// they hold an `error` key, e.g. a record of a DocStore. Empty bodies, such
+/// as those of `204 No Content` responses, decode to `Value::Null`
+fn decode_response(status: u16, body: &[u8]) -> Result<Value, Error> {
+    let failed = status >= 400;
+
+    let value = if body.iter().all(u8::is_ascii_whitespace) {
+        Value::Null
+    } else {
+        match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(_) if failed => Value::String(String::from_utf8_lossy(body).into_owned()),
+            Err(err) => return Err(err.into()),
+        }
+    };
+
+    if failed {
+        return Err(Error::from_response(status, value));
+    }
+    Ok(value)
 }
 
+mod address;
+mod auth;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod builder;
 mod client;
+mod database;
+mod error;
+mod events;
+mod handles;
+mod instrument;
+mod iterator;
+#[cfg(feature = "metrics")]
+mod metrics;
+mod middleware;
+#[cfg(any(test, feature = "testing"))]
+pub mod mock;
+mod query;
+mod retry;
+mod runtime;
+mod sender;
+pub mod transport;
 
 /// The types of OrbitDB databases
-#[derive(Debug, ToString, EnumString)]
+///
+/// Serialized as their lowercase name, e.g. `docstore`, without the index of a DocStore
+#[derive(Debug, Clone, PartialEq, ToString, EnumString)]
 #[strum(serialize_all = "lowercase")]
 pub enum DatabaseType {
     EventLog,
@@ -70,43 +167,29 @@ pub enum DatabaseType {
     KeyValue,
     Counter,
 }
+
+impl Serialize for DatabaseType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for DatabaseType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| serde::de::Error::custom(format!("unknown database type `{}`", name)))
+    }
+}
 #[derive(Debug, Serialize)]
 pub struct AccessController {
     pub r#type: String,
     pub write: Vec<String>,
 }
-#[derive(Debug, Serialize)]
-pub struct Query {
-    pub propname: Option<String>,
-    pub comp: Option<Comparison>,
-    pub values: Vec<i64>,
-}
-#[derive(Debug, ToString, Serialize)]
-#[strum(serialize_all = "lowercase")]
-pub enum Comparison {
-    /// ==
-    EQ,
-    /// !=
-    NE,
-    /// >
-    GT,
-    /// <
-    LT,
-    /// >=
-    GTE,
-    /// <=
-    LTE,
-    /// %
-    Mod,
-    /// Those between min and max
-    Range,
-    /// *
-    All,
-}
 
 /// Unit tests for the client methods
 ///
-/// These tests currently expect the REST api server
-/// to be running on `https://localhost:3000`
-#[cfg(test)]
+/// The tests run against a `mock::MockServer` of their own, through the
+/// default transport
+#[cfg(all(test, any(feature = "surf", feature = "reqwest", feature = "hyper")))]
 mod tests;
@@ -0,0 +1,93 @@
+//! Prometheus metrics of the traffic of a `Client`, behind the `metrics` cargo feature
+
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use std::time::Duration;
+
+/// Per operation counters and latencies of the requests made by `Client`
+/// methods, registered in a prometheus `Registry`
+///
+/// The metrics, labelled by the name of the `Client` method, are:
+///
+/// - `orbitdb_client_requests_total`: the requests made
+/// - `orbitdb_client_request_failures_total`: the failed requests, also
+///   labelled by the `Error::kind` of their error
+/// - `orbitdb_client_request_duration_seconds`: the latency of the requests,
+///   retries included
+/// - `orbitdb_client_sent_bytes_total`: the bytes of the request bodies
+/// - `orbitdb_client_received_bytes_total`: the bytes of the response bodies
+///
+/// Cloned metrics share their values, so that several clients can be
+/// registered together
+#[derive(Clone)]
+pub struct Metrics {
+    requests: IntCounterVec,
+    failures: IntCounterVec,
+    duration: HistogramVec,
+    sent_bytes: IntCounterVec,
+    received_bytes: IntCounterVec,
+}
+
+impl Metrics {
+    /// Creates the metrics, registering them in `registry`
+    ///
+    /// Fails if metrics of the same names are already registered
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let metrics = Metrics {
+            requests: IntCounterVec::new(
+                Opts::new("orbitdb_client_requests_total", "Requests made"),
+                &["operation"],
+            )?,
+            failures: IntCounterVec::new(
+                Opts::new("orbitdb_client_request_failures_total", "Failed requests"),
+                &["operation", "kind"],
+            )?,
+            duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "orbitdb_client_request_duration_seconds",
+                    "Request latencies in seconds",
+                ),
+                &["operation"],
+            )?,
+            sent_bytes: IntCounterVec::new(
+                Opts::new("orbitdb_client_sent_bytes_total", "Bytes of request bodies"),
+                &["operation"],
+            )?,
+            received_bytes: IntCounterVec::new(
+                Opts::new(
+                    "orbitdb_client_received_bytes_total",
+                    "Bytes of response bodies",
+                ),
+                &["operation"],
+            )?,
+        };
+
+        registry.register(Box::new(metrics.requests.clone()))?;
+        registry.register(Box::new(metrics.failures.clone()))?;
+        registry.register(Box::new(metrics.duration.clone()))?;
+        registry.register(Box::new(metrics.sent_bytes.clone()))?;
+        registry.register(Box::new(metrics.received_bytes.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Records a request of `operation` that took `duration`, failing
+    /// with an error of kind `failure` if any
+    pub(crate) fn request(&self, operation: &str, duration: Duration, failure: Option<&str>) {
+        self.requests.with_label_values(&[operation]).inc();
+        self.duration
+            .with_label_values(&[operation])
+            .observe(duration.as_secs_f64());
+        if let Some(kind) = failure {
+            self.failures.with_label_values(&[operation, kind]).inc();
+        }
+    }
+
+    /// Records the bytes of the bodies of a request of `operation` and of its response
+    pub(crate) fn bytes(&self, operation: &str, sent: usize, received: usize) {
+        self.sent_bytes
+            .with_label_values(&[operation])
+            .inc_by(sent as u64);
+        self.received_bytes
+            .with_label_values(&[operation])
+            .inc_by(received as u64);
+    }
+}
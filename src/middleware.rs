@@ -0,0 +1,45 @@
+//! Hooks inspecting and modifying the requests of a `Client` and their responses
+
+use super::*;
+use std::sync::Arc;
+use transport::{Request, Response};
+
+/// Intercepts the requests sent by a `Client` and the responses it receives
+///
+/// Middleware registered with `ClientBuilder::middleware` see the requests
+/// in the order they were registered, once the default headers and
+/// credentials are added, and the responses in the reverse order, before
+/// their body is decoded. Each retried or re-authenticated attempt goes
+/// through them again.
+///
+/// Event subscriptions only go through the request hooks, their responses
+/// being streamed
+pub trait Middleware: Send + Sync {
+    /// Inspects or modifies `request` before it is sent, e.g. to add a
+    /// header or rewrite its url
+    ///
+    /// Returning an error aborts the request
+    fn request(&self, request: &mut Request) -> Result<(), Error> {
+        let _ = request;
+        Ok(())
+    }
+
+    /// Inspects or modifies `response`, received for `request`, before it is decoded
+    ///
+    /// Returning an error fails the request
+    fn response(&self, request: &Request, response: &mut Response) -> Result<(), Error> {
+        let _ = (request, response);
+        Ok(())
+    }
+}
+
+/// Lets a middleware be shared, e.g. to inspect it while a `Client` uses it
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn request(&self, request: &mut Request) -> Result<(), Error> {
+        (**self).request(request)
+    }
+
+    fn response(&self, request: &Request, response: &mut Response) -> Result<(), Error> {
+        (**self).response(request, response)
+    }
+}
@@ -0,0 +1,723 @@
+//! An in-memory OrbitDB REST API server, for testing code using a `Client`
+//!
+//! Behind the `testing` cargo feature, `MockServer` serves the endpoints of
+//! the REST API on a local port, keeping its databases in memory. Each server
+//! has databases of its own, so tests can run in parallel with a server each.
+//!
+//! The stores follow OrbitDB's semantics: documents are indexed by their
+//! `indexBy` property and found by a case-insensitive substring of their key,
+//! log iterators select their entries as OrbitDB's do, and queries are
+//! evaluated with `Query::matches`. Event subscriptions are not served
+
+use super::*;
+use cid::multibase::Base;
+use cid::multihash::Multihash;
+use cid::Cid;
+use percent_encoding::percent_decode_str;
+use serde_json::json;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash as _, Hasher};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use url::Url;
+
+/// An OrbitDB REST API server running in the background until it is dropped
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Starts a server without any database on a free local port
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::new(addr.port())));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            thread::spawn(move || serve(listener, state, shutdown))
+        };
+
+        Ok(MockServer {
+            addr,
+            state,
+            shutdown,
+            thread: Some(thread),
+        })
+    }
+
+    /// The base url of the server
+    pub fn url(&self) -> Url {
+        Url::parse(&format!("http://{}/", self.addr)).expect("a socket address is a valid host")
+    }
+
+    /// A client of the server, sending its requests through the default transport
+    #[cfg(any(feature = "surf", feature = "reqwest", feature = "hyper"))]
+    pub fn client(&self) -> Client {
+        Client::new(self.url())
+    }
+
+    /// The id of the server's identity, given write access to the databases it creates
+    pub fn identity(&self) -> String {
+        self.state.lock().unwrap().identity.clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wakes the listener up so that it sees the shutdown
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Accepts connections until the server shuts down, handling each on a thread of its own
+fn serve(listener: TcpListener, state: Arc<Mutex<State>>, shutdown: Arc<AtomicBool>) {
+    for stream in listener.incoming() {
+        if shutdown.load(Ordering::SeqCst) {
+            break;
+        }
+        if let Ok(stream) = stream {
+            let state = state.clone();
+            thread::spawn(move || {
+                let _ = handle_connection(stream, &state);
+            });
+        }
+    }
+}
+
+/// Answers the request received on `stream`, then closes the connection
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let (status, body) = match read_request(&mut reader)? {
+        Some((method, path, body)) => match state.lock().unwrap().handle(&method, &path, &body) {
+            Ok(value) => (200, value),
+            Err((status, message)) => (status, error_body(status, &message)),
+        },
+        None => (400, error_body(400, "Invalid request")),
+    };
+
+    let body = body.to_string();
+    let reason = http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("");
+    let mut stream = stream;
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Reads the method, path and body of an HTTP/1.1 request, `None` if it is malformed
+fn read_request(reader: &mut impl BufRead) -> io::Result<Option<(String, String, Vec<u8>)>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Ok(None),
+    };
+
+    let mut length = 0;
+    let mut chunked = false;
+    loop {
+        line.clear();
+        reader.read_line(&mut line)?;
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case("Transfer-Encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+
+    let mut body = vec![];
+    if chunked {
+        loop {
+            line.clear();
+            reader.read_line(&mut line)?;
+            let size = match usize::from_str_radix(line.trim().split(';').next().unwrap_or(""), 16)
+            {
+                Ok(size) => size,
+                Err(_) => return Ok(None),
+            };
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk)?;
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    } else {
+        body.resize(length, 0);
+        reader.read_exact(&mut body)?;
+    }
+
+    let path = target.split('?').next().unwrap_or_default().to_string();
+    Ok(Some((method, path, body)))
+}
+
+/// The error envelope of the REST API
+fn error_body(status: u16, message: &str) -> Value {
+    let error = http::StatusCode::from_u16(status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Error");
+    json!({ "statusCode": status, "error": error, "message": message })
+}
+
+/// The status and message of a failed request
+type Failure = (u16, String);
+
+/// Fails with `status` and `message`
+fn fail<T>(status: u16, message: impl Into<String>) -> Result<T, Failure> {
+    Err((status, message.into()))
+}
+
+/// The databases of a server and the identity writing to them
+struct State {
+    identity: String,
+    dbs: BTreeMap<String, Db>,
+    /// The number of hashes generated so far, making each of them unique
+    hashes: u64,
+    /// Distinguishes the hashes of different servers
+    seed: u16,
+}
+
+/// An in-memory database
+struct Db {
+    dbtype: String,
+    index_by: String,
+    /// The options the database was created with, as reported by its information
+    options: Value,
+    write: Vec<String>,
+    root: String,
+    store: Store,
+    /// The hash of the last entry appended to the database's log, if any
+    head: Option<String>,
+    /// The number of entries appended to the database's log
+    clock: u64,
+}
+
+/// The contents of a database, depending on its type
+enum Store {
+    /// The entries of an EventLog or Feed, oldest first
+    Log(Vec<Value>),
+    /// The log entries putting each document, by key in insertion order
+    Docs(Vec<(String, Value)>),
+    /// The values stored by key, in insertion order
+    KeyValue(Vec<(String, Value)>),
+    /// The value of a counter
+    Counter(u64),
+}
+
+impl State {
+    fn new(seed: u16) -> Self {
+        let mut state = State {
+            identity: String::new(),
+            dbs: BTreeMap::new(),
+            hashes: 0,
+            seed,
+        };
+        // Public keys are 33 bytes long, hex encoded
+        let key: String = (0..4)
+            .map(|_| format!("{:016x}", state.next_u64()))
+            .collect();
+        state.identity = format!("03{}", &key[..64]);
+        state
+    }
+
+    /// A pseudo-random number, unique to the server and call
+    fn next_u64(&mut self) -> u64 {
+        self.hashes += 1;
+        let mut hasher = DefaultHasher::new();
+        (self.seed, self.hashes).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// A new CIDv1 of a dag-cbor sha2-256 block, base58btc encoded
+    fn next_hash(&mut self) -> String {
+        let mut digest = Vec::with_capacity(32);
+        for _ in 0..4 {
+            digest.extend_from_slice(&self.next_u64().to_be_bytes());
+        }
+        let hash = Multihash::wrap(0x12, &digest).expect("a sha2-256 digest fits a multihash");
+        Cid::new_v1(0x71, hash)
+            .to_string_of_base(Base::Base58Btc)
+            .expect("a CIDv1 is encodable in any base")
+    }
+
+    /// Handles a request, returning the json body of the response
+    fn handle(&mut self, method: &str, path: &str, body: &[u8]) -> Result<Value, Failure> {
+        let body: Value = if body.iter().all(u8::is_ascii_whitespace) {
+            Value::Null
+        } else {
+            match serde_json::from_slice(body) {
+                Ok(body) => body,
+                Err(err) => {
+                    return fail(400, format!("Invalid request payload JSON format: {}", err))
+                }
+            }
+        };
+        let mut segments: Vec<String> = path
+            .trim_matches('/')
+            .split('/')
+            .map(|segment| percent_decode_str(segment).decode_utf8_lossy().into_owned())
+            .collect();
+        if segments.len() > 1 && segments[0] == "db" {
+            segments[1] = self.resolve(&segments[1]);
+        }
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        match (method, segments.as_slice()) {
+            ("GET", ["dbs"]) => Ok(Value::Object(
+                self.dbs
+                    .iter()
+                    .map(|(name, db)| (name.clone(), db.info(name, &self.identity)))
+                    .collect(),
+            )),
+            ("GET", ["identity"]) => Ok(json!({
+                "id": self.identity,
+                "publicKey": self.identity,
+                "signatures": { "id": self.identity, "publicKey": self.identity },
+                "type": "orbitdb",
+            })),
+            ("POST", ["db", name]) => self.open(name, &body),
+            ("GET", ["db", name]) => Ok(self.db(name)?.info(name, &self.identity)),
+            ("DELETE", ["db", name]) => match self.dbs.remove(*name) {
+                Some(_) => Ok(json!({})),
+                None => not_found(name),
+            },
+            ("GET", ["db", name, "value"]) => match &self.db(name)?.store {
+                Store::Counter(value) => Ok(json!(value)),
+                _ => unsupported(self.db(name)?, "value"),
+            },
+            ("GET", ["db", name, "index"]) => Ok(self.db(name)?.index(&self.identity)),
+            ("GET", ["db", name, "iterator"]) => self.db(name)?.iterator(&body),
+            ("GET", ["db", name, item]) => self.db(name)?.get(item),
+            ("POST", ["db", name, "query"]) => self.db(name)?.query(body),
+            ("POST", ["db", name, "put"]) => self.append(name, "put", body),
+            ("POST", ["db", name, "add"]) => self.append(name, "add", body),
+            ("POST", ["db", name, "inc"]) => self.append(name, "inc", json!(1)),
+            ("POST", ["db", name, "inc", amount]) => match amount.parse::<u64>() {
+                Ok(amount) => self.append(name, "inc", json!(amount)),
+                Err(_) => fail(400, "Invalid request params input"),
+            },
+            ("DELETE", ["db", name, item]) => self.append(name, "remove", json!(item)),
+            ("POST", ["db", name, "access", "write"]) => {
+                let id = match body["id"].as_str() {
+                    Some(id) => id.to_string(),
+                    None => return fail(400, "Invalid request payload input"),
+                };
+                let hash = self.next_hash();
+                let db = self.db_mut(name)?;
+                if !db.write.contains(&id) {
+                    db.write.push(id);
+                }
+                Ok(json!({ "hash": hash }))
+            }
+            _ => fail(404, "Not Found"),
+        }
+    }
+
+    /// The name of the database `name`, which may be its full OrbitDB address
+    fn resolve(&self, name: &str) -> String {
+        if let Some((root, path)) = name
+            .strip_prefix("/orbitdb/")
+            .and_then(|address| address.split_once('/'))
+        {
+            if self.dbs.get(path).is_some_and(|db| db.root == root) {
+                return path.into();
+            }
+        }
+        name.into()
+    }
+
+    fn db(&self, name: &str) -> Result<&Db, Failure> {
+        match self.dbs.get(name) {
+            Some(db) => Ok(db),
+            None => not_found(name),
+        }
+    }
+
+    fn db_mut(&mut self, name: &str) -> Result<&mut Db, Failure> {
+        match self.dbs.get_mut(name) {
+            Some(db) => Ok(db),
+            None => not_found(name),
+        }
+    }
+
+    /// Opens the database `name`, creating it if asked to
+    fn open(&mut self, name: &str, options: &Value) -> Result<Value, Failure> {
+        let dbtype = options["type"].as_str();
+        let overwrite = options["overwrite"].as_bool().unwrap_or(false);
+        let create = options["create"].as_bool().unwrap_or(false);
+
+        if let Some(db) = self.dbs.get(name) {
+            if let Some(dbtype) = dbtype {
+                if dbtype != db.dbtype {
+                    return fail(
+                        500,
+                        format!(
+                            "Database '{}' is type '{}' but was opened as '{}'",
+                            db.address(name),
+                            db.dbtype,
+                            dbtype
+                        ),
+                    );
+                }
+            }
+            if !overwrite {
+                return Ok(db.info(name, &self.identity));
+            }
+        } else if !create {
+            return fail(404, format!("Database '{}' doesn't exist", name));
+        }
+
+        let dbtype = match dbtype.or_else(|| self.dbs.get(name).map(|db| db.dbtype.as_str())) {
+            Some(dbtype) => dbtype.to_string(),
+            None => return fail(400, "Database type not provided"),
+        };
+        let store = match dbtype.as_str() {
+            "eventlog" | "feed" => Store::Log(vec![]),
+            "docstore" => Store::Docs(vec![]),
+            "keyvalue" => Store::KeyValue(vec![]),
+            "counter" => Store::Counter(0),
+            _ => return fail(400, format!("Invalid database type '{}'", dbtype)),
+        };
+        let write = match options["accessController"]["write"].as_array() {
+            Some(write) => write
+                .iter()
+                .filter_map(Value::as_str)
+                .map(String::from)
+                .collect(),
+            None => vec![self.identity.clone()],
+        };
+
+        let db = Db {
+            dbtype,
+            index_by: options["indexBy"].as_str().unwrap_or("_id").into(),
+            options: json!({
+                "create": create,
+                "localOnly": options["localOnly"].as_bool().unwrap_or(false),
+                "maxHistory": options["maxHistory"].as_i64().unwrap_or(-1),
+                "overwrite": overwrite,
+                "replicate": options["replicate"].as_bool().unwrap_or(true),
+            }),
+            write,
+            root: self.next_hash(),
+            store,
+            head: None,
+            clock: 0,
+        };
+        let info = db.info(name, &self.identity);
+        self.dbs.insert(name.into(), db);
+        Ok(info)
+    }
+
+    /// Appends the entry of the operation `op` with the argument `arg` to
+    /// the log of the database `name`, returning its hash
+    fn append(&mut self, name: &str, op: &str, arg: Value) -> Result<Value, Failure> {
+        let identity = self.identity.clone();
+        let hash = self.next_hash();
+        let db = self.db_mut(name)?;
+        if !db.write.iter().any(|id| *id == identity || id == "*") {
+            return fail(
+                403,
+                format!(
+                    "Could not append entry, key \"{}\" is not allowed to write to the log",
+                    identity
+                ),
+            );
+        }
+
+        let address = db.address(name);
+        let (payload, key) = match (&mut db.store, op) {
+            (Store::Log(_), "add") => (json!({ "op": "ADD", "key": null, "value": arg }), None),
+            (Store::Log(entries), "remove") if db.dbtype == "feed" => {
+                let item = arg.as_str().unwrap_or_default();
+                match entries.iter().position(|entry| entry["hash"] == item) {
+                    Some(i) => entries.remove(i),
+                    None => {
+                        return fail(
+                            500,
+                            format!("No entry with hash '{}' in the database", item),
+                        )
+                    }
+                };
+                (json!({ "op": "DEL", "key": item, "value": null }), None)
+            }
+            (Store::Docs(_), "put") => {
+                let key = match &arg[&db.index_by] {
+                    Value::Null => {
+                        return fail(
+                            500,
+                            format!(
+                                "The provided document doesn't contain field '{}'",
+                                db.index_by
+                            ),
+                        )
+                    }
+                    key => query::js_string(key),
+                };
+                (json!({ "op": "PUT", "key": key, "value": arg }), Some(key))
+            }
+            (Store::Docs(docs), "remove") => {
+                let key = arg.as_str().unwrap_or_default();
+                match docs.iter().position(|(doc, _)| doc == key) {
+                    Some(i) => docs.remove(i),
+                    None => {
+                        return fail(500, format!("No entry with key '{}' in the database", key))
+                    }
+                };
+                (json!({ "op": "DEL", "key": key, "value": null }), None)
+            }
+            (Store::KeyValue(values), "put") => {
+                let key = match &arg["key"] {
+                    Value::Null => return fail(400, "Invalid request payload input"),
+                    key => query::js_string(key),
+                };
+                let value = arg["value"].clone();
+                match values.iter_mut().find(|(stored, _)| *stored == key) {
+                    Some((_, stored)) => *stored = value.clone(),
+                    None => values.push((key.clone(), value.clone())),
+                }
+                (json!({ "op": "PUT", "key": key, "value": value }), None)
+            }
+            (Store::KeyValue(values), "remove") => {
+                let key = arg.as_str().unwrap_or_default();
+                values.retain(|(stored, _)| stored != key);
+                (json!({ "op": "DEL", "key": key, "value": null }), None)
+            }
+            (Store::Counter(value), "inc") => {
+                *value += arg.as_u64().unwrap_or(1);
+                let counter = json!({ "id": identity, "counters": { identity.clone(): *value } });
+                (
+                    json!({ "op": "COUNTER", "key": null, "value": counter }),
+                    None,
+                )
+            }
+            _ => return unsupported(db, op),
+        };
+
+        db.clock += 1;
+        let entry = json!({
+            "hash": hash,
+            "id": address,
+            "payload": payload,
+            "next": db.head.iter().collect::<Vec<_>>(),
+            "v": 1,
+            "clock": { "id": identity, "time": db.clock },
+        });
+        db.head = Some(hash.clone());
+
+        match (&mut db.store, key) {
+            (Store::Log(entries), _) if op == "add" => entries.push(entry),
+            (Store::Docs(docs), Some(key)) => match docs.iter_mut().find(|(doc, _)| *doc == key) {
+                Some((_, stored)) => *stored = entry,
+                None => docs.push((key, entry)),
+            },
+            _ => (),
+        }
+        Ok(json!({ "hash": hash }))
+    }
+}
+
+impl Db {
+    /// The OrbitDB address of the database
+    fn address(&self, name: &str) -> String {
+        format!("/orbitdb/{}/{}", self.root, name)
+    }
+
+    /// The information returned about the database
+    fn info(&self, name: &str, identity: &str) -> Value {
+        let capabilities: &[&str] = match self.dbtype.as_str() {
+            "eventlog" => &["add", "get", "iterator"],
+            "feed" => &["add", "get", "iterator", "remove"],
+            "docstore" => &["get", "put", "query", "remove"],
+            "keyvalue" => &["get", "put", "remove"],
+            _ => &["inc", "value"],
+        };
+        let mut options = self.options.clone();
+        options["indexBy"] = if self.dbtype == "docstore" {
+            json!(self.index_by)
+        } else {
+            Value::Null
+        };
+        json!({
+            "address": { "root": self.root, "path": name },
+            "dbname": name,
+            "id": self.address(name),
+            "options": options,
+            "canAppend": self.write.iter().any(|id| id == identity || id == "*"),
+            "write": self.write,
+            "type": self.dbtype,
+            "capabilities": capabilities,
+        })
+    }
+
+    /// The index of the database, as kept by OrbitDB
+    fn index(&self, identity: &str) -> Value {
+        match &self.store {
+            Store::Log(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|entry| {
+                        (
+                            entry["hash"].as_str().unwrap_or_default().into(),
+                            entry.clone(),
+                        )
+                    })
+                    .collect(),
+            ),
+            Store::Docs(docs) => Value::Object(docs.iter().cloned().collect()),
+            Store::KeyValue(values) => Value::Object(values.iter().cloned().collect()),
+            Store::Counter(value) => json!({ "id": identity, "counters": { identity: value } }),
+        }
+    }
+
+    /// The items identified by `item`
+    fn get(&self, item: &str) -> Result<Value, Failure> {
+        match &self.store {
+            Store::Log(entries) => Ok(Value::Array(
+                entries
+                    .iter()
+                    .filter(|entry| entry["hash"] == item)
+                    .cloned()
+                    .collect(),
+            )),
+            Store::Docs(docs) => {
+                // Keys are matched case-insensitively, by substring
+                let terms = item.split(' ').count();
+                let normalize = |key: &str| {
+                    if terms > 1 {
+                        key.replace('.', " ").to_lowercase()
+                    } else {
+                        key.to_lowercase()
+                    }
+                };
+                let item = normalize(item);
+                Ok(Value::Array(
+                    docs.iter()
+                        .filter(|(key, _)| normalize(key).contains(&item))
+                        .map(|(_, entry)| entry["payload"]["value"].clone())
+                        .collect(),
+                ))
+            }
+            Store::KeyValue(values) => Ok(Value::Array(
+                values
+                    .iter()
+                    .filter(|(key, _)| key == item)
+                    .map(|(_, value)| value.clone())
+                    .collect(),
+            )),
+            Store::Counter(_) => unsupported(self, "get"),
+        }
+    }
+
+    /// The documents matching the query `body`
+    fn query(&self, body: Value) -> Result<Value, Failure> {
+        let docs = match &self.store {
+            Store::Docs(docs) => docs,
+            _ => return unsupported(self, "query"),
+        };
+        let query: Query = match serde_json::from_value(body) {
+            Ok(query) => query,
+            Err(err) => return fail(400, format!("Invalid request payload input: {}", err)),
+        };
+        if let Err(err) = query.validate() {
+            return fail(400, err.to_string());
+        }
+
+        Ok(Value::Array(
+            docs.iter()
+                .map(|(_, entry)| &entry["payload"]["value"])
+                .filter(|doc| query.matches(doc))
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    /// The log entries selected by the iterator options `options`, as
+    /// OrbitDB's EventStore selects them
+    fn iterator(&self, options: &Value) -> Result<Value, Failure> {
+        let entries = match &self.store {
+            Store::Log(entries) => entries,
+            _ => return unsupported(self, "iterator"),
+        };
+        let hash = |bound: &str| options[bound].as_str().filter(|hash| !hash.is_empty());
+        // A missing or zero limit returns a single entry, a negative one every entry
+        let amount = match options["limit"].as_i64() {
+            Some(limit) if limit < 0 => entries.len(),
+            Some(limit) if limit > 0 => limit as usize,
+            _ => 1,
+        };
+
+        let mut result = if hash("gt").is_some() || hash("gte").is_some() {
+            let bound = hash("gt").or_else(|| hash("gte"));
+            read(entries.iter(), bound, amount, hash("gte").is_some())
+        } else {
+            // The newest entries are searched first
+            let bound = hash("lt").or_else(|| hash("lte"));
+            let inclusive = hash("lte").is_some() || hash("lt").is_none();
+            let mut result = read(entries.iter().rev(), bound, amount, inclusive);
+            result.reverse();
+            result
+        };
+        if options["reverse"].as_bool().unwrap_or(false) {
+            result.reverse();
+        }
+        Ok(Value::Array(result))
+    }
+}
+
+/// Reads `amount` entries of `entries` from the entry with hash `bound`,
+/// or from the first entry if it is not found, skipping it unless `inclusive`
+fn read<'a>(
+    entries: impl Iterator<Item = &'a Value> + Clone,
+    bound: Option<&str>,
+    amount: usize,
+    inclusive: bool,
+) -> Vec<Value> {
+    let index = bound
+        .and_then(|bound| entries.clone().position(|entry| entry["hash"] == bound))
+        .unwrap_or(0);
+    let start = if inclusive { index } else { index + 1 };
+
+    entries.skip(start).take(amount).cloned().collect()
+}
+
+fn not_found<T>(name: &str) -> Result<T, Failure> {
+    fail(404, format!("Database {} does not exist", name))
+}
+
+fn unsupported<T>(db: &Db, op: &str) -> Result<T, Failure> {
+    fail(
+        400,
+        format!("The {} database does not support '{}'", db.dbtype, op),
+    )
+}
@@ -0,0 +1,333 @@
+use super::*;
+use serde::Deserialize;
+
+/// A query applied to the documents of a database by `Client::db_query`
+///
+/// Queries are usually built from `Query::prop` or `Query::all`,
+/// which guarantee the right number of values for the comparison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Query {
+    /// The property compared, `_id` if not specified
+    pub propname: Option<String>,
+    /// The comparison applied, `Comparison::All` if not specified
+    pub comp: Option<Comparison>,
+    /// The operands of the comparison
+    pub values: Vec<Value>,
+}
+#[derive(Debug, Clone, Copy, PartialEq, ToString, EnumString, Serialize, Deserialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Comparison {
+    /// ==
+    EQ,
+    /// !=
+    NE,
+    /// >
+    GT,
+    /// <
+    LT,
+    /// >=
+    GTE,
+    /// <=
+    LTE,
+    /// %
+    Mod,
+    /// Those between min and max
+    Range,
+    /// *
+    All,
+}
+
+/// Builds a `Query` comparing a single property
+#[derive(Debug, Clone)]
+pub struct QueryBuilder {
+    propname: String,
+}
+
+impl Query {
+    /// Creates a query from its parts, validating the number and
+    /// types of values against the comparison
+    pub fn new(
+        propname: Option<String>,
+        comp: Option<Comparison>,
+        values: Vec<Value>,
+    ) -> Result<Self, Error> {
+        let query = Query {
+            propname,
+            comp,
+            values,
+        };
+
+        query.validate()?;
+        Ok(query)
+    }
+
+    /// Starts building a query comparing the property `propname`
+    pub fn prop(propname: &str) -> QueryBuilder {
+        QueryBuilder {
+            propname: propname.into(),
+        }
+    }
+
+    /// A query matching every document
+    pub fn all() -> Self {
+        Query {
+            propname: None,
+            comp: Some(Comparison::All),
+            values: vec![],
+        }
+    }
+
+    /// Checks that the values are valid operands for the comparison
+    pub fn validate(&self) -> Result<(), Error> {
+        let comp = self.comp.unwrap_or(Comparison::All);
+        let expected = match comp {
+            Comparison::All => 0,
+            Comparison::Mod | Comparison::Range => 2,
+            _ => 1,
+        };
+
+        if self.values.len() != expected {
+            return Err(Error::InvalidQuery(format!(
+                "`{}` expects {} value(s), got {}",
+                comp.to_string(),
+                expected,
+                self.values.len()
+            )));
+        }
+
+        // The server coerces the operands, e.g. those of `mod` with `%`,
+        // so only arrays and objects cannot be compared against
+        for value in &self.values {
+            if value.is_array() || value.is_object() {
+                return Err(Error::InvalidQuery(format!(
+                    "`{}` cannot compare against {}",
+                    comp.to_string(),
+                    value
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `document` matches the query
+    ///
+    /// This follows the REST API's query endpoint, which compares
+    /// `document[propname]` against the values using JavaScript's
+    /// loose (`==`) and relational (`<`, `>=`, ...) operators
+    pub fn matches(&self, document: &Value) -> bool {
+        let propname = self.propname.as_deref().unwrap_or("_id");
+        let prop = Primitive::from(document.get(propname));
+        let value = |i: usize| Primitive::from(self.values.get(i));
+
+        match self.comp.unwrap_or(Comparison::All) {
+            Comparison::EQ => loose_eq(&prop, &value(0)),
+            Comparison::NE => !loose_eq(&prop, &value(0)),
+            Comparison::GT => less_than(&value(0), &prop) == Some(true),
+            Comparison::LT => less_than(&prop, &value(0)) == Some(true),
+            Comparison::GTE => less_than(&prop, &value(0)) == Some(false),
+            Comparison::LTE => less_than(&value(0), &prop) == Some(false),
+            Comparison::Mod => {
+                let remainder = prop.to_number() % value(0).to_number();
+                loose_eq(&Primitive::Number(remainder), &value(1))
+            }
+            Comparison::Range => {
+                let (a, b) = (value(0).to_number(), value(1).to_number());
+                // Math.max and Math.min propagate NaN
+                let (min, max) = if a.is_nan() || b.is_nan() {
+                    (f64::NAN, f64::NAN)
+                } else {
+                    (a.min(b), a.max(b))
+                };
+
+                less_than(&Primitive::Number(max), &prop) == Some(false)
+                    && less_than(&prop, &Primitive::Number(min)) == Some(false)
+            }
+            Comparison::All => true,
+        }
+    }
+
+    /// The documents matching the query, in their original order
+    pub fn filter<'a>(&self, documents: &'a [Value]) -> Vec<&'a Value> {
+        documents.iter().filter(|doc| self.matches(doc)).collect()
+    }
+}
+
+/// A json value converted to a JavaScript primitive,
+/// where a missing property is `undefined`
+enum Primitive {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+impl From<Option<&Value>> for Primitive {
+    fn from(value: Option<&Value>) -> Self {
+        match value {
+            None => Primitive::Undefined,
+            Some(Value::Null) => Primitive::Null,
+            Some(Value::Bool(b)) => Primitive::Bool(*b),
+            Some(Value::Number(n)) => Primitive::Number(n.as_f64().unwrap_or(f64::NAN)),
+            Some(Value::String(s)) => Primitive::String(s.clone()),
+            Some(other) => Primitive::String(js_string(other)),
+        }
+    }
+}
+
+impl Primitive {
+    /// JavaScript's `ToNumber`
+    fn to_number(&self) -> f64 {
+        match self {
+            Primitive::Undefined => f64::NAN,
+            Primitive::Null => 0.0,
+            Primitive::Bool(b) => *b as u8 as f64,
+            Primitive::Number(n) => *n,
+            Primitive::String(s) => js_parse_number(s),
+        }
+    }
+}
+
+/// JavaScript's `ToString` for arrays, objects and numbers
+pub(crate) fn js_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => js_number_string(n.as_f64().unwrap_or(f64::NAN)),
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(js_string).collect::<Vec<_>>().join(","),
+        Value::Object(_) => String::from("[object Object]"),
+    }
+}
+
+/// JavaScript's `ToString` for numbers, writing integers without a fraction
+pub(crate) fn js_number_string(n: f64) -> String {
+    if n.is_nan() {
+        String::from("NaN")
+    } else if n.is_infinite() {
+        String::from(if n > 0.0 { "Infinity" } else { "-Infinity" })
+    } else if n.fract() == 0.0 && n.abs() < 1e21 {
+        format!("{}", n as i128)
+    } else {
+        n.to_string()
+    }
+}
+
+/// JavaScript's `ToNumber` applied to a string
+fn js_parse_number(s: &str) -> f64 {
+    let s = s.trim();
+    let radix = |prefix: &str, radix| {
+        s.strip_prefix(prefix).map(|digits| {
+            match digits.chars().all(|c| c.is_ascii_alphanumeric()) {
+                true => u64::from_str_radix(digits, radix).map_or(f64::NAN, |n| n as f64),
+                false => f64::NAN,
+            }
+        })
+    };
+
+    if s.is_empty() {
+        return 0.0;
+    }
+    if let Some(n) = radix("0x", 16)
+        .or_else(|| radix("0X", 16))
+        .or_else(|| radix("0o", 8))
+        .or_else(|| radix("0O", 8))
+        .or_else(|| radix("0b", 2))
+        .or_else(|| radix("0B", 2))
+    {
+        return n;
+    }
+    match s.trim_start_matches(&['+', '-'][..]) {
+        "Infinity" if s.starts_with('-') => f64::NEG_INFINITY,
+        "Infinity" => f64::INFINITY,
+        // Rust also accepts spellings of inf and NaN that JavaScript does not
+        digits
+            if digits
+                .chars()
+                .any(|c| c.is_ascii_alphabetic() && c != 'e' && c != 'E') =>
+        {
+            f64::NAN
+        }
+        _ => s.parse().unwrap_or(f64::NAN),
+    }
+}
+
+/// JavaScript's loose equality, `a == b`
+fn loose_eq(a: &Primitive, b: &Primitive) -> bool {
+    use Primitive::*;
+
+    match (a, b) {
+        (Undefined, Undefined) | (Null, Null) | (Undefined, Null) | (Null, Undefined) => true,
+        (Undefined, _) | (Null, _) | (_, Undefined) | (_, Null) => false,
+        (String(a), String(b)) => a == b,
+        (Bool(a), Bool(b)) => a == b,
+        _ => a.to_number() == b.to_number(),
+    }
+}
+
+/// JavaScript's abstract relational comparison, `a < b`,
+/// which is undefined (`None`) when either side is NaN
+fn less_than(a: &Primitive, b: &Primitive) -> Option<bool> {
+    if let (Primitive::String(a), Primitive::String(b)) = (a, b) {
+        // Strings compare by UTF-16 code units
+        return Some(a.encode_utf16().lt(b.encode_utf16()));
+    }
+
+    let (a, b) = (a.to_number(), b.to_number());
+    if a.is_nan() || b.is_nan() {
+        None
+    } else {
+        Some(a < b)
+    }
+}
+
+impl QueryBuilder {
+    fn compare(self, comp: Comparison, values: Vec<Value>) -> Query {
+        Query {
+            propname: Some(self.propname),
+            comp: Some(comp),
+            values,
+        }
+    }
+
+    /// Matches documents whose property equals `value`
+    pub fn eq(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::EQ, vec![value.into()])
+    }
+
+    /// Matches documents whose property does not equal `value`
+    pub fn ne(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::NE, vec![value.into()])
+    }
+
+    /// Matches documents whose property is greater than `value`
+    pub fn gt(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::GT, vec![value.into()])
+    }
+
+    /// Matches documents whose property is less than `value`
+    pub fn lt(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::LT, vec![value.into()])
+    }
+
+    /// Matches documents whose property is greater than or equal to `value`
+    pub fn gte(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::GTE, vec![value.into()])
+    }
+
+    /// Matches documents whose property is less than or equal to `value`
+    pub fn lte(self, value: impl Into<Value>) -> Query {
+        self.compare(Comparison::LTE, vec![value.into()])
+    }
+
+    /// Matches documents whose property modulo `divisor` equals `remainder`
+    pub fn modulo(self, divisor: impl Into<Value>, remainder: impl Into<Value>) -> Query {
+        self.compare(Comparison::Mod, vec![divisor.into(), remainder.into()])
+    }
+
+    /// Matches documents whose property lies between `min` and `max`, inclusive
+    pub fn range(self, min: impl Into<Value>, max: impl Into<Value>) -> Query {
+        self.compare(Comparison::Range, vec![min.into(), max.into()])
+    }
+}
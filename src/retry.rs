@@ -0,0 +1,126 @@
+//! Retries of requests failing with transient errors
+
+use super::*;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+use transport::Response;
+
+/// When and how often a `Client` retries its failed requests
+///
+/// A request is retried when its connection fails, it times out or is answered
+/// with one of the retryable statuses, waiting an exponentially growing
+/// backoff between attempts.
+///
+/// Requests appending an entry or creating a database (`db_add`, `db_put`,
+/// `inc_counter_value`, `grant_write_access`, `create_db` and `open_db`) are
+/// only retried once `retry_non_idempotent` is set, as a request failing
+/// after reaching the server would otherwise take effect twice
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// The number of attempts made for a request, the first one included
+    pub max_attempts: u32,
+    /// The wait before the first retry, doubled for each following one
+    pub initial_backoff: Duration,
+    /// The longest wait between two attempts
+    pub max_backoff: Duration,
+    /// Whether each wait is randomly shortened by up to half, so that
+    /// clients failing together do not retry together
+    pub jitter: bool,
+    /// The response statuses worth retrying
+    pub statuses: Vec<u16>,
+    /// Whether requests appending an entry or creating a database are retried
+    pub non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            statuses: vec![408, 429, 502, 503, 504],
+            non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Three attempts, backing off from 100ms up to 10s with jitter, on
+    /// `408`, `429`, `502`, `503` and `504` responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes up to `max_attempts` attempts for each request, the first one included
+    pub fn max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Waits `initial` before the first retry, doubling the wait for each
+    /// following one up to `max`
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Randomly shortens each wait by up to half when `jitter` is true
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Retries the responses with one of `statuses` instead of the default ones
+    pub fn statuses(mut self, statuses: &[u16]) -> Self {
+        self.statuses = statuses.to_vec();
+        self
+    }
+
+    /// Also retries the requests appending an entry or creating a database
+    /// when `retry` is true, at the risk of them taking effect twice
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.non_idempotent = retry;
+        self
+    }
+
+    /// Whether the request answered with `result` on attempt `attempt` is tried again
+    pub(crate) fn retries(&self, attempt: u32, result: &Result<Response, Error>) -> bool {
+        attempt < self.max_attempts
+            && match result {
+                Ok(response) => self.statuses.contains(&response.status),
+                Err(Error::Connection(_)) | Err(Error::Timeout(_)) => true,
+                Err(_) => false,
+            }
+    }
+
+    /// The wait after the failed attempt `attempt`
+    ///
+    /// A `Retry-After` delay in seconds sent by the server replaces the
+    /// backoff, without exceeding the longest wait
+    pub(crate) fn backoff_after(&self, attempt: u32, result: &Result<Response, Error>) -> Duration {
+        let retry_after = match result {
+            Ok(response) => response
+                .header("Retry-After")
+                .and_then(|seconds| seconds.trim().parse().ok())
+                .map(Duration::from_secs),
+            Err(_) => None,
+        };
+        if let Some(retry_after) = retry_after {
+            return retry_after.min(self.max_backoff);
+        }
+
+        let backoff = self
+            .initial_backoff
+            .checked_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff);
+        if !self.jitter {
+            return backoff;
+        }
+        let random = RandomState::new().build_hasher().finish();
+        backoff / 2 + backoff.mul_f64((random % 1000) as f64 / 2000.0)
+    }
+}
@@ -0,0 +1,67 @@
+//! The async runtime the client's timers run on
+//!
+//! Selected by the `tokio` and `async-std` cargo features, tokio being
+//! used when both are enabled
+
+use crate::Error;
+use futures::future::{self, Either};
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(not(any(feature = "tokio", feature = "async-std")))]
+compile_error!("either the `tokio` or the `async-std` feature must be enabled");
+
+#[cfg(all(any(feature = "reqwest", feature = "hyper"), not(feature = "tokio")))]
+compile_error!("the `reqwest` and `hyper` transports require the `tokio` feature");
+
+/// Waits until `duration` has elapsed
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "tokio")]
+    tokio::time::sleep(duration).await;
+    #[cfg(all(not(feature = "tokio"), feature = "async-std"))]
+    async_std::task::sleep(duration).await;
+}
+
+/// Runs `future`, failing with `Error::Timeout` if it takes longer than `duration`
+pub(crate) async fn timeout<F: Future>(
+    duration: Option<Duration>,
+    future: F,
+) -> Result<F::Output, Error> {
+    let duration = match duration {
+        Some(duration) => duration,
+        None => return Ok(future.await),
+    };
+
+    match future::select(Box::pin(future), Box::pin(sleep(duration))).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Error::Timeout(duration)),
+    }
+}
+
+/// Runs futures to completion from synchronous code
+#[cfg(feature = "blocking")]
+pub(crate) struct Runtime {
+    #[cfg(feature = "tokio")]
+    runtime: tokio::runtime::Runtime,
+}
+
+#[cfg(feature = "blocking")]
+impl Runtime {
+    /// Starts the runtime, a single threaded one for tokio
+    pub(crate) fn new() -> Result<Self, std::io::Error> {
+        Ok(Runtime {
+            #[cfg(feature = "tokio")]
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?,
+        })
+    }
+
+    /// Blocks the current thread until `future` completes
+    pub(crate) fn block_on<F: Future>(&self, future: F) -> F::Output {
+        #[cfg(feature = "tokio")]
+        return self.runtime.block_on(future);
+        #[cfg(all(not(feature = "tokio"), feature = "async-std"))]
+        async_std::task::block_on(future)
+    }
+}
@@ -0,0 +1,153 @@
+//! Sends the requests of a `Client` through its transport, applying its configuration
+
+use super::*;
+use auth::Auth;
+use builder::Timeouts;
+use middleware::Middleware;
+use retry::RetryPolicy;
+use std::sync::Arc;
+use transport::{Request, Response, StreamingResponse};
+
+/// The transport of a `Client` along with what is added to each of its requests
+pub(crate) struct Sender {
+    /// The transport sending the HTTP requests
+    pub(crate) transport: Arc<dyn Transport>,
+    /// The headers sent with every request
+    pub(crate) headers: Vec<(String, String)>,
+    /// The time limits of every request
+    pub(crate) timeouts: Timeouts,
+    /// The credentials sent with every request
+    pub(crate) auth: Option<Auth>,
+    /// The retries of failed requests, if any
+    pub(crate) retry: Option<RetryPolicy>,
+    /// The middleware every request and response goes through, in order
+    pub(crate) middleware: Vec<Arc<dyn Middleware>>,
+    /// The metrics recording the client's requests, if any
+    #[cfg(feature = "metrics")]
+    pub(crate) metrics: Option<Metrics>,
+}
+
+impl Sender {
+    /// A sender adding nothing to the requests sent through `transport`
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Sender {
+            transport,
+            headers: vec![],
+            timeouts: Timeouts::default(),
+            auth: None,
+            retry: None,
+            middleware: vec![],
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Sends `request`, returning the response once its whole body is received
+    ///
+    /// Failed requests are retried according to the retry policy, those
+    /// that are not `idempotent` only if the policy allows it
+    pub(crate) async fn send(&self, request: Request, idempotent: bool) -> Result<Response, Error> {
+        let policy = match &self.retry {
+            Some(policy) if idempotent || policy.non_idempotent => policy,
+            _ => return self.send_once(request).await,
+        };
+
+        let mut attempt = 1;
+        loop {
+            let result = self.send_once(request.clone()).await;
+            if !policy.retries(attempt, &result) {
+                return result;
+            }
+            instrument::retry(attempt, &result);
+            runtime::sleep(policy.backoff_after(attempt, &result)).await;
+            attempt += 1;
+        }
+    }
+
+    /// Sends `request` once, or twice if its token is refreshed
+    async fn send_once(&self, request: Request) -> Result<Response, Error> {
+        let resend = self.refresh_request(&request);
+        let request = self.prepare(request).await?;
+        let response = self.exchange(request).await?;
+
+        match resend {
+            Some(request) if response.status == 401 => {
+                let request = self.refresh(request).await?;
+                self.exchange(request).await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// Sends the prepared `request` through the transport, passing its
+    /// response through the middleware
+    async fn exchange(&self, request: Request) -> Result<Response, Error> {
+        if self.middleware.is_empty() {
+            return self.timeouts.send(&*self.transport, request).await;
+        }
+
+        let mut response = self
+            .timeouts
+            .send(&*self.transport, request.clone())
+            .await?;
+        for middleware in self.middleware.iter().rev() {
+            middleware.response(&request, &mut response)?;
+        }
+        Ok(response)
+    }
+
+    /// Sends `request`, returning the response as soon as its headers are received
+    pub(crate) async fn send_streaming(
+        &self,
+        request: Request,
+    ) -> Result<StreamingResponse, Error> {
+        let resend = self.refresh_request(&request);
+        let request = self.prepare(request).await?;
+        let response = self
+            .timeouts
+            .send_streaming(&*self.transport, request)
+            .await?;
+
+        match resend {
+            Some(request) if response.status == 401 => {
+                let request = self.refresh(request).await?;
+                self.timeouts
+                    .send_streaming(&*self.transport, request)
+                    .await
+            }
+            _ => Ok(response),
+        }
+    }
+
+    /// A copy of `request` to send again with a refreshed token if it is rejected
+    fn refresh_request(&self, request: &Request) -> Option<Request> {
+        match &self.auth {
+            Some(auth) if auth.refreshable() => Some(request.clone()),
+            _ => None,
+        }
+    }
+
+    /// Adds the default headers and credentials to `request`, then passes
+    /// it through the middleware
+    async fn prepare(&self, mut request: Request) -> Result<Request, Error> {
+        let mut headers = self.headers.clone();
+        if let Some(auth) = &self.auth {
+            headers.push(("Authorization".into(), auth.header().await?));
+        }
+        headers.append(&mut request.headers);
+
+        request.headers = headers;
+        for middleware in &self.middleware {
+            middleware.request(&mut request)?;
+        }
+        Ok(request)
+    }
+
+    /// Refreshes the rejected token, adding the new one to `request`
+    async fn refresh(&self, request: Request) -> Result<Request, Error> {
+        if let Some(auth) = &self.auth {
+            auth.refresh().await?;
+        }
+        self.prepare(request).await
+    }
+}
@@ -1,24 +1,36 @@
 use super::*;
 use serde_json::json;
-use surf::Exception;
-
-fn client() -> Result<Client, url::ParseError> {
-    Ok(Client::new(url::Url::parse("https://localhost:3000")?))
+use std::convert::TryFrom;
+use std::time::Duration;
+
+#[cfg(all(not(feature = "tokio"), feature = "async-std"))]
+use async_attributes::test as async_test;
+#[cfg(feature = "tokio")]
+use tokio::test as async_test;
+
+/// Any error returned by a test
+type Exception = Box<dyn std::error::Error + Send + Sync>;
+
+/// A client of a mock server of its own, which runs until it is dropped
+fn client() -> Result<(mock::MockServer, Client), std::io::Error> {
+    let server = mock::MockServer::start()?;
+    let client = server.client();
+    Ok((server, client))
 }
 
 /// Tests `client.get_dbs()`
-#[async_attributes::test]
+#[async_test]
 async fn get_dbs() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
 
     client.get_dbs().await?;
     Ok(())
 }
 
 /// Tests success of `client.get_db(:dbname)`
-#[async_attributes::test]
+#[async_test]
 async fn get_db_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("feed");
 
     client
@@ -32,19 +44,133 @@ async fn get_db_ok() -> Result<(), Exception> {
     Ok(())
 }
 
+/// Tests the metadata returned by `client.get_db(:dbname)`, and its serialization
+#[async_test]
+async fn get_db_metadata() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("docstore");
+
+    client
+        .create_db(
+            &dbname,
+            DatabaseType::DocStore {
+                index_by: Some("name".into()),
+            },
+            None,
+            false,
+        )
+        .await?;
+
+    // Tested function
+    let db = client.get_db(&dbname).await?;
+    assert_eq!(db.dbname, dbname);
+    assert_eq!(db.id, format!("/orbitdb/{}/{}", db.address.root, dbname));
+    assert_eq!(db.r#type, DatabaseType::DocStore { index_by: None });
+    assert_eq!(db.options.index_by.as_deref(), Some("name"));
+    assert!(db.capabilities.contains(&Capability::Query));
+    assert!(!db.capabilities.contains(&Capability::Add));
+
+    let json = serde_json::to_value(&db)?;
+    assert_eq!(json["type"], "docstore");
+    assert_eq!(json["options"]["indexBy"], "name");
+    assert_eq!(serde_json::from_value::<Database>(json)?, db);
+
+    let unknown: Vec<Capability> = serde_json::from_value(json!(["put", "compact"]))?;
+    assert_eq!(unknown, [Capability::Put, Capability::Other]);
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests parsing, formatting and serialization of `OrbitDbAddress`
+#[test]
+fn orbitdb_address() -> Result<(), Exception> {
+    let v0 = "/orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u/feed";
+    let address: OrbitDbAddress = v0.parse()?;
+    assert_eq!(
+        address.root(),
+        "QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u"
+    );
+    assert_eq!(address.name(), "feed");
+    assert_eq!(address.to_string(), v0);
+
+    let v1 = "/orbitdb/bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi/docs/2020";
+    let address: OrbitDbAddress = serde_json::from_value(json!(v1))?;
+    assert_eq!(address.name(), "docs/2020");
+    assert_eq!(serde_json::to_value(&address)?, json!(v1));
+
+    for invalid in &[
+        "feed",
+        "orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u/feed",
+        "/orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u",
+        "/orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u/",
+        "/orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8/feed",
+        "/orbitdb/zdpuNotACid/feed",
+    ] {
+        match invalid.parse::<OrbitDbAddress>() {
+            Err(Error::InvalidAddress(_)) => (),
+            other => panic!(
+                "expected Error::InvalidAddress for {}, got {:?}",
+                invalid, other
+            ),
+        }
+    }
+    assert!(serde_json::from_value::<OrbitDbAddress>(json!("/orbitdb/feed")).is_err());
+    Ok(())
+}
+
+/// Tests that a full `OrbitDbAddress` is accepted in place of a database name
+#[async_test]
+async fn orbitdb_address_as_dbname() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("feed");
+
+    let db = client
+        .create_db(&dbname, DatabaseType::Feed, None, false)
+        .await?;
+    let address = OrbitDbAddress::try_from(&db.address)?;
+    assert_eq!(address.as_str(), db.id);
+
+    // Tested function
+    assert_eq!(client.get_db(address.as_ref()).await?, db);
+    client.db_add(address.as_ref(), "entry").await?;
+    let entries: Vec<String> = client.feed(address.as_ref()).iterator(Some(-1)).await?;
+    assert_eq!(entries, ["entry"]);
+
+    let other = OrbitDbAddress::new(address.root(), "other")?;
+    assert!(matches!(
+        client.get_db(other.as_ref()).await,
+        Err(Error::NotFound(_))
+    ));
+
+    client.delete_db(address.as_ref()).await?;
+    Ok(())
+}
+
 /// Tests failure of `client.get_db(:dbname)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn get_db_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
     client.get_db("fake").await.unwrap();
 }
 
+/// Tests that `client.get_db(:dbname)` reports a missing database as `Error::NotFound`
+#[async_test]
+async fn get_db_not_found() {
+    let (_server, client) = client().unwrap();
+
+    match client.get_db("fake").await {
+        Err(Error::NotFound(_)) => (),
+        other => panic!("expected Error::NotFound, got {:?}", other),
+    }
+}
+
 /// Tests success of `client.get_counter_value(:dbname)`
-#[async_attributes::test]
+#[async_test]
 async fn get_counter_value_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("counter2");
 
     client
@@ -58,18 +184,18 @@ async fn get_counter_value_ok() -> Result<(), Exception> {
 }
 
 /// Tests failure of `client.get_counter_value(:dbname)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn get_counter_value_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
     client.get_counter_value("fake").await.unwrap();
 }
 
 /// Tests success of `client.get_db_item(:dbname, :item)`
-#[async_attributes::test]
+#[async_test]
 async fn get_db_item_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
     let record = json!({ "_id": 1, "value": "test" });
 
@@ -84,25 +210,78 @@ async fn get_db_item_ok() -> Result<(), Exception> {
 
     client.db_put(&dbname, &record).await?;
 
-    assert_eq!(client.get_db_item(&dbname, "1").await?, vec![record]);
+    assert_eq!(
+        client.get_db_item::<Value>(&dbname, "1").await?,
+        vec![record]
+    );
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests that numeric keys are stringified by the mock server as by `Query::matches`
+#[async_test]
+async fn get_db_item_large_key() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("docstore");
+    let record = json!({ "_id": 1e19, "value": "large" });
+
+    client
+        .create_db(
+            &dbname,
+            DatabaseType::DocStore { index_by: None },
+            None,
+            false,
+        )
+        .await?;
+    client.db_put(&dbname, &record).await?;
+
+    let key = "10000000000000000000";
+    assert!(Query::prop("_id").eq(key).matches(&record));
+    assert_eq!(
+        client.get_db_item::<Value>(&dbname, key).await?,
+        vec![record]
+    );
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests that item keys are percent-encoded into a single path segment
+#[async_test]
+async fn get_db_item_escaped_key() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("keyvalue");
+    let key = "a/b?c#d%20e f";
+
+    client
+        .create_db(&dbname, DatabaseType::KeyValue, None, false)
+        .await?;
+
+    let keyvalue = client.keyvalue::<String>(&dbname);
+    keyvalue.put(key, &"value".to_string()).await?;
+    assert_eq!(keyvalue.get(key).await?.as_deref(), Some("value"));
+    assert_eq!(keyvalue.get("a").await?, None);
+    keyvalue.delete(key).await?;
+    assert_eq!(keyvalue.get(key).await?, None);
 
     client.delete_db(&dbname).await?;
     Ok(())
 }
 
 /// Tests failure of `client.get_db_item(:dbname, :item)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn get_db_item_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
-    client.get_db_item("fake", "item").await.unwrap();
+    client.get_db_item::<Value>("fake", "item").await.unwrap();
 }
 
 /// Tests success of `client.get_db_iterator(:dbname)`
-#[async_attributes::test]
+#[async_test]
 async fn get_db_iterator_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("feed");
 
     client
@@ -110,26 +289,26 @@ async fn get_db_iterator_ok() -> Result<(), Exception> {
         .await?;
 
     // Tested function
-    client.get_db_iterator(&dbname, None).await?;
+    client.get_db_iterator::<Value>(&dbname, None).await?;
 
     client.delete_db(&dbname).await?;
     Ok(())
 }
 
 /// Tests failure of `client.get_db_iterator(:dbname)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn get_db_iterator_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
-    client.get_db_iterator("fake", None).await.unwrap();
+    client.get_db_iterator::<Value>("fake", None).await.unwrap();
 }
 
 /// Tests `client.get_db_index(:dbname)`
 // TODO add failure scenario
-#[async_attributes::test]
+#[async_test]
 async fn get_db_index() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
     let record = json!({ "_id": 1, "value": "test" });
 
@@ -151,9 +330,9 @@ async fn get_db_index() -> Result<(), Exception> {
 }
 
 /// Tests `client.get_identity()`
-#[async_attributes::test]
+#[async_test]
 async fn get_identity() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
 
     client.get_identity().await?;
     Ok(())
@@ -165,9 +344,9 @@ async fn get_identity() -> Result<(), Exception> {
 ///            :access-controller,
 ///            :overwrite
 ///        )`
-#[async_attributes::test]
+#[async_test]
 async fn create_db() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
 
     // Tested function
@@ -186,9 +365,9 @@ async fn create_db() -> Result<(), Exception> {
 
 /// Tests `client.db_query(:dbname, :query)`
 // TODO: add failure scenario
-#[async_attributes::test]
+#[async_test]
 async fn db_query() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
     let record = json!({ "_id": 1, "value": "test" });
     let query = Query {
@@ -208,16 +387,19 @@ async fn db_query() -> Result<(), Exception> {
 
     client.db_put(&dbname, &record).await?;
 
-    assert_eq!(client.db_query(&dbname, query).await?, vec![record]);
+    assert_eq!(
+        client.db_query::<Value>(&dbname, query).await?,
+        vec![record]
+    );
 
     client.delete_db(&dbname).await?;
     Ok(())
 }
 
 /// Tests success of `client.db_add(:dbname, :entry)`
-#[async_attributes::test]
+#[async_test]
 async fn db_add_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("eventlog");
 
     client
@@ -232,18 +414,18 @@ async fn db_add_ok() -> Result<(), Exception> {
 }
 
 /// Tests failure of `client.db_add(:dbname, :entry)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn db_add_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
     client.db_add("fake", "entry").await.unwrap();
 }
 
 /// Tests success of `client.inc_counter_value(:dbname, :value)`
-#[async_attributes::test]
+#[async_test]
 async fn inc_counter_value_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("counter");
 
     client
@@ -258,18 +440,18 @@ async fn inc_counter_value_ok() -> Result<(), Exception> {
 }
 
 /// Tests failure of `client.inc_counter_value(:dbname, :value)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn inc_counter_value_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
     client.inc_counter_value("fake", None).await.unwrap();
 }
 
 /// Tests success of `client.db_put(:dbname, :record)`
-#[async_attributes::test]
+#[async_test]
 async fn db_put_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
     let record = json!({ "_id": 1, "value": "test" });
 
@@ -290,19 +472,19 @@ async fn db_put_ok() -> Result<(), Exception> {
 }
 
 /// Tests failure of `client.db_put(:dbname, :record)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn db_put_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
     let record = json!({ "_id": 1, "value": "test" });
 
     client.db_put("fake", &record).await.unwrap();
 }
 
 /// Tests `client.delete_db(:dbname)`
-#[async_attributes::test]
+#[async_test]
 async fn delete_db() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
 
     client
@@ -319,9 +501,9 @@ async fn delete_db() -> Result<(), Exception> {
 }
 
 /// Tests success of `client.delete_db_item(:dbname, :item)`
-#[async_attributes::test]
+#[async_test]
 async fn delete_db_item_ok() -> Result<(), Exception> {
-    let client = client()?;
+    let (_server, client) = client()?;
     let dbname = String::from("docstore");
     let record = json!({ "_id": 1, "value": "test" });
 
@@ -343,10 +525,1195 @@ async fn delete_db_item_ok() -> Result<(), Exception> {
 }
 
 /// Tests failure of `client.delete_db_item(:dbname, :item)`
-#[async_attributes::test]
+#[async_test]
 #[should_panic]
 async fn delete_db_item_err() {
-    let client = client().unwrap();
+    let (_server, client) = client().unwrap();
 
     client.delete_db_item("fake", "item").await.unwrap();
 }
+
+/// Tests putting and getting documents through `client.docstore::<T>(:dbname)`
+#[async_test]
+async fn docstore_handle() -> Result<(), Exception> {
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Record {
+        _id: String,
+        value: String,
+    }
+
+    let (_server, client) = client()?;
+    let dbname = String::from("docstore");
+    let record = Record {
+        _id: "1".into(),
+        value: "test".into(),
+    };
+
+    client
+        .create_db(
+            &dbname,
+            DatabaseType::DocStore { index_by: None },
+            None,
+            false,
+        )
+        .await?;
+
+    // Tested handle
+    let docstore = client.docstore::<Record>(&dbname);
+    docstore.put(&record).await?;
+    assert_eq!(docstore.get("1").await?, vec![record]);
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests adding and reading back entries through `client.eventlog::<T>(:dbname)`
+/// and `client.feed::<T>(:dbname)`
+#[async_test]
+async fn log_handles() -> Result<(), Exception> {
+    #[derive(Debug, PartialEq, Serialize, serde::Deserialize)]
+    struct Event {
+        name: String,
+        count: u32,
+    }
+
+    let (_server, client) = client()?;
+    let first = Event {
+        name: "first".into(),
+        count: 1,
+    };
+    let second = Event {
+        name: "second".into(),
+        count: 2,
+    };
+
+    client
+        .create_db("eventlog", DatabaseType::EventLog, None, false)
+        .await?;
+    client
+        .create_db("feed", DatabaseType::Feed, None, false)
+        .await?;
+
+    // Tested handles
+    let eventlog = client.eventlog::<Event>("eventlog");
+    let hash = eventlog.add(&first).await?;
+    eventlog.add(&second).await?;
+    assert_eq!(eventlog.get(&hash.hash).await?, vec![first]);
+    assert_eq!(
+        eventlog.iterator(IteratorOptions::new().limit(1)).await?,
+        vec![second]
+    );
+
+    let feed = client.feed::<Event>("feed");
+    let hash = feed
+        .add(&Event {
+            name: "removed".into(),
+            count: 0,
+        })
+        .await?;
+    feed.remove(&hash.hash).await?;
+    assert_eq!(feed.iterator(Some(-1)).await?, vec![]);
+
+    client.delete_db("eventlog").await?;
+    client.delete_db("feed").await?;
+    Ok(())
+}
+
+/// Tests incrementing through `client.counter(:dbname)`
+#[async_test]
+async fn counter_handle() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("counter3");
+
+    client
+        .create_db(&dbname, DatabaseType::Counter, None, false)
+        .await?;
+
+    // Tested handle
+    let counter = client.counter(&dbname);
+    counter.inc(Some(2)).await?;
+    assert_eq!(counter.value().await?, 2);
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests parsing of a well formed OrbitDB error envelope
+#[test]
+fn error_response_envelope() {
+    let raw = json!({
+        "statusCode": 404,
+        "error": "Not Found",
+        "message": "Database does not exist",
+        "details": { "dbname": "fake" },
+    });
+    let response = ErrorResponse::from_value(raw.clone());
+
+    assert_eq!(response.status_code, Some(404));
+    assert_eq!(response.error.as_deref(), Some("Not Found"));
+    assert_eq!(response.message, "Database does not exist");
+    assert_eq!(response.details, Some(json!({ "dbname": "fake" })));
+    assert_eq!(response.raw, raw);
+}
+
+/// Tests that malformed error envelopes are parsed without panicking
+#[test]
+fn error_response_malformed() {
+    let response = ErrorResponse::from_value(json!({ "error": true, "message": 42 }));
+    assert_eq!(response.status_code, None);
+    assert_eq!(response.message, "42");
+
+    let response = ErrorResponse::from_value(json!({ "error": "Bad Request" }));
+    assert_eq!(response.message, "Bad Request");
+
+    let response = ErrorResponse::from_value(json!({ "error": null, "statusCode": "500" }));
+    assert_eq!(response.status_code, Some(500));
+    assert_eq!(response.message, "unknown error");
+}
+
+/// Tests that empty `204 No Content` bodies decode to null
+#[test]
+fn decode_response_no_content() -> Result<(), Error> {
+    assert_eq!(decode_response(204, b"")?, Value::Null);
+    Ok(())
+}
+
+/// Tests that error statuses with non-json bodies map into server errors
+#[test]
+fn decode_response_error_status() {
+    match decode_response(500, b"upstream crashed") {
+        Err(Error::Server(response)) => {
+            assert_eq!(response.status_code, Some(500));
+            assert_eq!(response.message, "upstream crashed");
+        }
+        other => panic!("expected Error::Server, got {:?}", other),
+    }
+
+    match decode_response(404, b"") {
+        Err(Error::NotFound(response)) => assert_eq!(response.message, "Not Found"),
+        other => panic!("expected Error::NotFound, got {:?}", other),
+    }
+}
+
+/// Tests that successful bodies are not mistaken for error envelopes
+#[test]
+fn decode_response_success() -> Result<(), Error> {
+    let record = json!({ "_id": "1", "error": "timeout", "message": "retry later" });
+    assert_eq!(decode_response(200, record.to_string().as_bytes())?, record);
+    Ok(())
+}
+
+/// Tests that `client.request(...)` returns the status of successful responses
+#[async_test]
+async fn request_status() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(
+        StaticTransport::new(201, json!({ "error": false })).then(204, Value::Null),
+    );
+    let client =
+        Client::with_transport(url::Url::parse("http://localhost:3000")?, transport.clone());
+
+    let created = client
+        .request::<Value>(RequestType::Post, "db/feed", &json!({ "create": true }))
+        .await?;
+    assert_eq!(created.status, 201);
+    assert_eq!(created.value, json!({ "error": false }));
+
+    let deleted = client
+        .request::<()>(RequestType::Delete, "db/feed", &Value::Null)
+        .await?;
+    assert_eq!(deleted.status, 204);
+    Ok(())
+}
+
+/// Tests that the query builder produces the OrbitDB query body
+#[test]
+fn query_builder() -> Result<(), serde_json::Error> {
+    assert_eq!(
+        serde_json::to_value(Query::prop("age").gte(18))?,
+        json!({ "propname": "age", "comp": "gte", "values": [18] })
+    );
+    assert_eq!(
+        serde_json::to_value(Query::prop("price").range(0.5, 9.99))?,
+        json!({ "propname": "price", "comp": "range", "values": [0.5, 9.99] })
+    );
+    assert_eq!(
+        serde_json::to_value(Query::all())?,
+        json!({ "propname": null, "comp": "all", "values": [] })
+    );
+    Ok(())
+}
+
+/// Tests validation of the values against each comparison
+#[test]
+fn query_validate() {
+    assert!(Query::prop("name").eq("test").validate().is_ok());
+    assert!(Query::prop("n").modulo(2, 0).validate().is_ok());
+    assert!(Query::new(None, None, vec![]).is_ok());
+
+    assert!(Query::new(None, Some(Comparison::EQ), vec![]).is_err());
+    assert!(Query::new(None, Some(Comparison::Range), vec![json!(1)]).is_err());
+    assert!(Query::new(None, Some(Comparison::All), vec![json!(1)]).is_err());
+    assert!(Query::prop("n").modulo("2", 0).validate().is_ok());
+    assert!(Query::prop("n").modulo(2, json!([0])).validate().is_err());
+    assert!(Query::prop("n").eq(json!({ "a": 1 })).validate().is_err());
+}
+
+/// The documents the local query evaluation tests run against
+fn query_documents() -> Vec<Value> {
+    vec![
+        json!({ "_id": 1, "age": 17, "name": "ann" }),
+        json!({ "_id": 2, "age": "18", "name": "bob" }),
+        json!({ "_id": 3, "age": 30.5, "name": "Cy" }),
+        json!({ "_id": "4", "age": null }),
+        json!({ "_id": 5, "age": [20] }),
+    ]
+}
+
+/// The `_id`s of the documents matching `query`
+fn query_ids(query: Query) -> Vec<Value> {
+    query
+        .filter(&query_documents())
+        .into_iter()
+        .map(|doc| doc["_id"].clone())
+        .collect()
+}
+
+/// Tests that equality is JavaScript's loose `==`
+#[test]
+fn query_matches_eq() {
+    assert_eq!(query_ids(Query::prop("age").eq(18)), vec![json!(2)]);
+    assert_eq!(query_ids(Query::prop("age").eq("20")), vec![json!(5)]);
+    assert_eq!(query_ids(Query::prop("_id").eq(4)), vec![json!("4")]);
+    // undefined == null
+    assert_eq!(
+        query_ids(
+            Query::new(
+                Some("missing".into()),
+                Some(Comparison::EQ),
+                vec![Value::Null]
+            )
+            .unwrap()
+        ),
+        query_ids(Query::all())
+    );
+    assert_eq!(
+        query_ids(Query::prop("name").ne("bob")),
+        vec![json!(1), json!(3), json!("4"), json!(5)]
+    );
+}
+
+/// Tests the relational comparisons, where NaN never matches
+#[test]
+fn query_matches_relational() {
+    assert_eq!(
+        query_ids(Query::prop("age").gte(18)),
+        vec![json!(2), json!(3), json!(5)]
+    );
+    assert_eq!(
+        query_ids(Query::prop("age").lt(18)),
+        vec![json!(1), json!("4")]
+    );
+    assert_eq!(query_ids(Query::prop("age").gt(30)), vec![json!(3)]);
+    assert_eq!(
+        query_ids(Query::prop("age").lte(17)),
+        vec![json!(1), json!("4")]
+    );
+    // Strings compare lexicographically, "Cy" < "ann"
+    assert_eq!(
+        query_ids(Query::prop("name").lt("b")),
+        vec![json!(1), json!(3)]
+    );
+    // undefined converts to NaN
+    assert_eq!(query_ids(Query::prop("missing").lt(1)), Vec::<Value>::new());
+    assert_eq!(
+        query_ids(Query::prop("missing").gte(1)),
+        Vec::<Value>::new()
+    );
+}
+
+/// Tests `Comparison::Mod`, `Comparison::Range` and `Comparison::All`
+#[test]
+fn query_matches_mod_range_all() {
+    assert_eq!(
+        query_ids(Query::prop("_id").modulo(2, 1)),
+        vec![json!(1), json!(3), json!(5)]
+    );
+    assert_eq!(query_ids(Query::prop("age").modulo(2, 0.5)), vec![json!(3)]);
+    // The operands are coerced like JavaScript's `%` and `==` do
+    assert_eq!(
+        query_ids(Query::prop("_id").modulo("2", "1")),
+        vec![json!(1), json!(3), json!(5)]
+    );
+    // The bounds are inclusive and may be given in either order
+    assert_eq!(
+        query_ids(Query::prop("age").range(20, 17)),
+        vec![json!(1), json!(2), json!(5)]
+    );
+    assert_eq!(query_ids(Query::all()).len(), 5);
+    assert_eq!(query_ids(Query::new(None, None, vec![]).unwrap()).len(), 5);
+}
+
+/// Tests decoding of `text/event-stream` bodies split across chunks
+#[test]
+fn event_stream_decoder() {
+    let mut decoder = events::Decoder::default();
+
+    assert_eq!(decoder.decode(b": keep-alive\r\nevent: wri"), vec![]);
+    let messages =
+        decoder.decode(b"te\r\nid: 1\r\ndata: {\"a\":\r\ndata: 1}\r\n\r\nretry: 500\n\n");
+    assert_eq!(
+        messages,
+        vec![events::Message {
+            id: Some("1".into()),
+            event: Some("write".into()),
+            data: "{\"a\":\n1}".into(),
+        }]
+    );
+    assert_eq!(decoder.retry, Some(Duration::from_millis(500)));
+
+    // The last event id carries over to the following messages
+    let messages = decoder.decode(b"data:plain\n\n");
+    assert_eq!(
+        messages,
+        vec![events::Message {
+            id: Some("1".into()),
+            event: None,
+            data: "plain".into(),
+        }]
+    );
+}
+
+/// Tests the names of the subscribable events
+#[test]
+fn event_kind_names() {
+    assert_eq!(
+        EventKind::ReplicateProgress.to_string(),
+        "replicate.progress"
+    );
+    assert_eq!("write".parse::<EventKind>(), Ok(EventKind::Write));
+    assert!("registered".parse::<EventKind>().is_err());
+}
+
+/// Tests that `client.subscribe(:dbname, :events)` refuses to subscribe to no events
+#[test]
+fn subscribe_no_events() -> Result<(), Exception> {
+    let client = Client::with_transport(
+        url::Url::parse("http://localhost:3000")?,
+        StaticTransport::new(200, json!({})),
+    );
+
+    assert!(matches!(
+        client.subscribe("feed", &[]),
+        Err(Error::InvalidQuery(_))
+    ));
+    Ok(())
+}
+
+/// Tests paging through `client.get_db_iterator(:dbname, :options)` by hash
+#[async_test]
+async fn get_db_iterator_options() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("eventlog2");
+
+    client
+        .create_db(&dbname, DatabaseType::EventLog, None, false)
+        .await?;
+    for entry in &["a", "b", "c"] {
+        client.db_add(&dbname, entry).await?;
+    }
+
+    // Tested function
+    let first: Vec<Value> = client
+        .get_db_iterator(&dbname, IteratorOptions::new().limit(2))
+        .await?;
+    assert_eq!(first.len(), 2);
+
+    // Pages are in chronological order, the oldest entry coming first
+    let hash = first[0]["hash"].as_str().unwrap_or_default();
+    let rest: Vec<Value> = client
+        .get_db_iterator(&dbname, IteratorOptions::new().lt(hash))
+        .await?;
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0]["payload"]["value"], json!("a"));
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests `client.stream_log(:dbname, :page_size)` across several pages
+#[async_test]
+async fn stream_log() -> Result<(), Exception> {
+    use futures::TryStreamExt;
+
+    let (_server, client) = client()?;
+    let dbname = String::from("eventlog3");
+
+    client
+        .create_db(&dbname, DatabaseType::EventLog, None, false)
+        .await?;
+    for entry in &["a", "b", "c", "d", "e"] {
+        client.db_add(&dbname, entry).await?;
+    }
+
+    // Tested function, with pages splitting the log unevenly, evenly,
+    // in single entries and in a page larger than the log
+    for page_size in 1..=6 {
+        let entries: Vec<Value> = client.stream_log(&dbname, page_size).try_collect().await?;
+        let values: Vec<&Value> = entries
+            .iter()
+            .map(|entry| &entry["payload"]["value"])
+            .collect();
+        assert_eq!(values, ["e", "d", "c", "b", "a"], "page size {}", page_size);
+    }
+
+    // Tested handle, yielding the values of the entries
+    let values: Vec<String> = client
+        .eventlog::<String>(&dbname)
+        .stream(2)
+        .try_collect()
+        .await?;
+    assert_eq!(values, ["e", "d", "c", "b", "a"]);
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// A transport answering every request with the same response,
+/// recording the requests it was sent
+struct StaticTransport {
+    /// The responses still to be sent, the last one being repeated
+    responses: std::sync::Mutex<Vec<transport::Response>>,
+    requests: std::sync::Mutex<Vec<transport::Request>>,
+}
+
+impl StaticTransport {
+    fn new(status: u16, body: Value) -> Self {
+        StaticTransport {
+            responses: Default::default(),
+            requests: Default::default(),
+        }
+        .then(status, body)
+    }
+
+    /// Queues another response, sent after the previous ones
+    fn then(self, status: u16, body: Value) -> Self {
+        self.responses.lock().unwrap().push(transport::Response {
+            status,
+            headers: vec![],
+            body: serde_json::to_vec(&body).unwrap(),
+        });
+        self
+    }
+
+    /// Adds a header to the last queued response
+    fn header(self, name: &str, value: &str) -> Self {
+        if let Some(response) = self.responses.lock().unwrap().last_mut() {
+            response.headers.push((name.into(), value.into()));
+        }
+        self
+    }
+}
+
+impl Transport for StaticTransport {
+    fn send(
+        &self,
+        request: transport::Request,
+    ) -> futures::future::BoxFuture<'_, Result<transport::Response, Error>> {
+        self.requests.lock().unwrap().push(request);
+        let mut responses = self.responses.lock().unwrap();
+        let response = if responses.len() > 1 {
+            responses.remove(0)
+        } else {
+            responses[0].clone()
+        };
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// Tests that requests are sent through the transport given to `Client::with_transport`
+#[async_test]
+async fn with_transport() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({ "hash": "zdpu" })));
+    let client =
+        Client::with_transport(url::Url::parse("http://localhost:3000")?, transport.clone());
+
+    client.db_add("eventlog", "entry").await?;
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].method, RequestType::Post);
+    assert_eq!(
+        requests[0].url.as_str(),
+        "http://localhost:3000/db/eventlog/add"
+    );
+    assert_eq!(requests[0].body, Some(b"\"entry\"".to_vec()));
+    Ok(())
+}
+
+/// Tests that the last segment of a base url lacking a trailing slash is kept
+#[async_test]
+async fn base_url_trailing_slash() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({ "hash": "zdpu" })));
+    let client = Client::with_transport(
+        url::Url::parse("http://localhost:3000/proxy")?,
+        transport.clone(),
+    );
+
+    client.db_add("eventlog", "entry").await?;
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(
+        requests[0].url.as_str(),
+        "http://localhost:3000/proxy/db/eventlog/add"
+    );
+    Ok(())
+}
+
+/// Tests the request sent by `client.open_db(:address, :options)`
+#[async_test]
+async fn open_db_request() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({})));
+    let client =
+        Client::with_transport(url::Url::parse("http://localhost:3000")?, transport.clone());
+    let address: OrbitDbAddress =
+        "/orbitdb/QmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u/docs".parse()?;
+    let options = OpenOptions::new()
+        .dbtype(DatabaseType::DocStore {
+            index_by: Some("name".into()),
+        })
+        .local_only(false)
+        .replicate(true)
+        .max_history(10)
+        .directory("./orbitdb/docs")
+        .sync(true);
+
+    // The empty response is not a database
+    assert!(client.open_db(address.as_ref(), options).await.is_err());
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(
+        requests[0].url.as_str(),
+        "http://localhost:3000/db/%2Forbitdb%2FQmWATWQ7fVPP2EFGu71UkfnqhYXDYH566qy47CnJDgvs8u%2Fdocs"
+    );
+    let body: Value = serde_json::from_slice(requests[0].body.as_deref().unwrap_or_default())?;
+    assert_eq!(
+        body,
+        json!({
+            "type": "docstore",
+            "indexBy": "name",
+            "localOnly": false,
+            "replicate": true,
+            "maxHistory": 10,
+            "directory": "./orbitdb/docs",
+            "sync": true,
+        })
+    );
+    Ok(())
+}
+
+/// Tests opening databases with `client.open_db(:dbname, :options)`
+#[async_test]
+async fn open_db() -> Result<(), Exception> {
+    let (_server, client) = client()?;
+    let dbname = String::from("keyvalue");
+
+    match client.open_db(&dbname, OpenOptions::new()).await {
+        Err(Error::NotFound(_)) => (),
+        other => panic!("expected Error::NotFound, got {:?}", other),
+    }
+
+    let options = OpenOptions::new()
+        .create(true)
+        .dbtype(DatabaseType::KeyValue)
+        .replicate(false)
+        .max_history(10);
+    let db = client.open_db(&dbname, options).await?;
+    assert_eq!(db.r#type, DatabaseType::KeyValue);
+    assert!(db.options.create);
+    assert!(!db.options.replicate);
+    assert_eq!(db.options.max_history, 10);
+
+    // Opening an existing database by address does not create it anew
+    let address = OrbitDbAddress::try_from(&db.address)?;
+    assert_eq!(
+        client.open_db(address.as_ref(), OpenOptions::new()).await?,
+        db
+    );
+
+    client.delete_db(&dbname).await?;
+    Ok(())
+}
+
+/// Tests that `blocking::Client` runs requests to completion on its own runtime
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_client() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({ "hash": "zdpu" })));
+    let client = blocking::Client::with_transport(
+        url::Url::parse("http://localhost:3000")?,
+        transport.clone(),
+    )?;
+
+    client.db_put("docstore", &json!({ "_id": "1" }))?;
+    let index = client.get_db_index("docstore")?;
+
+    assert_eq!(index, json!({ "hash": "zdpu" }));
+    assert_eq!(transport.requests.lock().unwrap().len(), 2);
+    Ok(())
+}
+
+/// Tests the typed handles of `blocking::Client`
+#[cfg(feature = "blocking")]
+#[test]
+fn blocking_handles() -> Result<(), Exception> {
+    let server = mock::MockServer::start()?;
+    let client = blocking::Client::from_async(server.client())?;
+
+    client.create_db("feed", DatabaseType::Feed, None, false)?;
+    let feed = client.feed::<String>("feed");
+    feed.add(&"a".to_string())?;
+    let hash = feed.add(&"b".to_string())?;
+    assert_eq!(feed.get(&hash.hash)?, ["b"]);
+    let values: Vec<String> = feed.stream(1).collect::<Result<_, _>>()?;
+    assert_eq!(values, ["b", "a"]);
+
+    client.create_db("keyvalue", DatabaseType::KeyValue, None, false)?;
+    let keyvalue = client.keyvalue::<u32>("keyvalue");
+    keyvalue.put("answer", &42)?;
+    assert_eq!(keyvalue.get("answer")?, Some(42));
+
+    client.create_db("counter", DatabaseType::Counter, None, false)?;
+    let counter = client.counter("counter");
+    counter.inc(Some(3))?;
+    assert_eq!(counter.value()?, 3);
+    Ok(())
+}
+
+/// Tests that the headers, user agent and base path of a `ClientBuilder` are used by every request
+#[async_test]
+async fn client_builder() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({ "hash": "zdpu" })));
+    let client = Client::builder(url::Url::parse("http://localhost:3000/proxy")?)
+        .transport(transport.clone())
+        .header("X-Api-Key", "secret")
+        .user_agent("tests/1.0")
+        .base_path("/orbitdb/")
+        .build()?;
+
+    client.db_add("eventlog", "entry").await?;
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(
+        requests[0].url.as_str(),
+        "http://localhost:3000/proxy/orbitdb/db/eventlog/add"
+    );
+    assert_eq!(
+        requests[0].headers,
+        vec![
+            ("X-Api-Key".to_string(), "secret".to_string()),
+            ("User-Agent".to_string(), "tests/1.0".to_string()),
+            ("Content-Type".to_string(), "application/json".to_string()),
+        ]
+    );
+    Ok(())
+}
+
+/// A transport whose responses take `delay` to arrive
+struct SlowTransport {
+    delay: Duration,
+}
+
+impl Transport for SlowTransport {
+    fn send(
+        &self,
+        _: transport::Request,
+    ) -> futures::future::BoxFuture<'_, Result<transport::Response, Error>> {
+        Box::pin(async move {
+            runtime::sleep(self.delay).await;
+            Ok(transport::Response {
+                status: 200,
+                headers: vec![],
+                body: b"{}".to_vec(),
+            })
+        })
+    }
+}
+
+/// Tests that requests taking longer than the timeout of a `ClientBuilder` fail
+#[async_test]
+async fn client_builder_timeout() -> Result<(), Exception> {
+    let transport = SlowTransport {
+        delay: Duration::from_secs(5),
+    };
+    let client = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport)
+        .timeout(Duration::from_millis(10))
+        .build()?;
+
+    match client.get_db_index("docstore").await {
+        Err(Error::Timeout(timeout)) => assert_eq!(timeout, Duration::from_millis(10)),
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+    Ok(())
+}
+
+/// The `Authorization` header of a recorded request
+fn authorization(request: &transport::Request) -> Option<&str> {
+    request
+        .headers
+        .iter()
+        .find(|(name, _)| name == "Authorization")
+        .map(|(_, value)| value.as_str())
+}
+
+/// Tests that static bearer and basic credentials are sent with every request
+#[async_test]
+async fn auth_static() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({})));
+    let bearer = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .auth(Auth::bearer("token"))
+        .build()?;
+    let basic = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .auth(Auth::basic("user", Some("pass")))
+        .build()?;
+
+    bearer.get_db_index("docstore").await?;
+    basic.get_db_index("docstore").await?;
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(authorization(&requests[0]), Some("Bearer token"));
+    assert_eq!(authorization(&requests[1]), Some("Basic dXNlcjpwYXNz"));
+    Ok(())
+}
+
+/// Tests that provided tokens are fetched once, then refreshed when rejected
+#[async_test]
+async fn auth_provider_refresh() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(
+        StaticTransport::new(200, json!({}))
+            .then(401, json!({ "message": "token expired" }))
+            .then(200, json!({})),
+    );
+    let fetched = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = fetched.clone();
+    let client = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .auth(Auth::provider(move || {
+            let count = counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move { Ok(format!("token-{}", count)) }
+        }))
+        .build()?;
+
+    client.get_db_index("docstore").await?;
+    client.get_db_index("docstore").await?;
+
+    let requests = transport.requests.lock().unwrap();
+    let tokens: Vec<_> = requests.iter().map(authorization).collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Some("Bearer token-0"),
+            Some("Bearer token-0"),
+            Some("Bearer token-1")
+        ]
+    );
+    Ok(())
+}
+
+/// Tests that TLS settings are refused for a transport they cannot be applied to
+#[test]
+fn tls_given_transport() -> Result<(), Exception> {
+    let client = Client::builder(url::Url::parse("https://localhost:3000")?)
+        .transport(StaticTransport::new(200, json!({})))
+        .tls(TlsConfig::new().danger_accept_invalid_certs(true))
+        .build();
+
+    assert!(matches!(client, Err(Error::Config(_))));
+    Ok(())
+}
+
+/// Tests that the root certificate bundles of a `TlsConfig` are split into their certificates
+#[cfg(any(feature = "reqwest", feature = "hyper"))]
+#[test]
+fn tls_certificate_bundles() {
+    let bundle = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n\
+                  -----BEGIN CERTIFICATE-----\nMIIC\n-----END CERTIFICATE-----\n";
+    let tls = TlsConfig::new()
+        .add_root_certificate(bundle.as_bytes())
+        .add_root_certificate(b"-----BEGIN CERTIFICATE-----\nMIID\n-----END CERTIFICATE-----");
+
+    let certificates: Vec<String> = tls
+        .certificates()
+        .into_iter()
+        .map(|pem| String::from_utf8(pem).unwrap())
+        .collect();
+    assert_eq!(
+        certificates,
+        vec![
+            "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----",
+            "-----BEGIN CERTIFICATE-----\nMIIC\n-----END CERTIFICATE-----",
+            "-----BEGIN CERTIFICATE-----\nMIID\n-----END CERTIFICATE-----",
+        ]
+    );
+}
+
+/// Tests that idempotent requests are retried on retryable statuses, and
+/// requests appending an entry only once allowed
+#[async_test]
+async fn retry_policy() -> Result<(), Exception> {
+    let policy = RetryPolicy::new().backoff(Duration::from_millis(1), Duration::from_millis(1));
+    let transport = std::sync::Arc::new(
+        StaticTransport::new(503, json!({}))
+            .then(200, json!({}))
+            .then(503, json!({}))
+            .then(503, json!({}))
+            .then(200, json!({ "hash": "zdpu" })),
+    );
+    let client = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .retry(policy.clone())
+        .build()?;
+    let appending = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .retry(policy.retry_non_idempotent(true))
+        .build()?;
+
+    client.get_db_index("docstore").await?;
+    assert_eq!(transport.requests.lock().unwrap().len(), 2);
+
+    assert_eq!(
+        client
+            .db_add("eventlog", "entry")
+            .await
+            .unwrap_err()
+            .status(),
+        Some(503)
+    );
+    assert_eq!(transport.requests.lock().unwrap().len(), 3);
+
+    appending.db_add("eventlog", "entry").await?;
+    assert_eq!(transport.requests.lock().unwrap().len(), 5);
+    Ok(())
+}
+
+/// Tests the exponential backoff between attempts, capped by its maximum
+#[test]
+fn retry_backoff() {
+    let policy = RetryPolicy::new()
+        .backoff(Duration::from_millis(100), Duration::from_millis(300))
+        .jitter(false);
+    let failed = Err(Error::Timeout(Duration::from_secs(1)));
+
+    let backoffs: Vec<_> = (1..=3)
+        .map(|attempt| policy.backoff_after(attempt, &failed))
+        .collect();
+    assert_eq!(
+        backoffs,
+        vec![
+            Duration::from_millis(100),
+            Duration::from_millis(200),
+            Duration::from_millis(300)
+        ]
+    );
+    assert!(policy.retries(2, &failed));
+    assert!(!policy.retries(3, &failed));
+
+    let jittered = policy.jitter(true).backoff_after(2, &failed);
+    assert!(jittered >= Duration::from_millis(100) && jittered <= Duration::from_millis(200));
+}
+
+/// Tests that connection failures and timeouts are retried, unlike the other errors
+#[test]
+fn retry_errors() {
+    let policy = RetryPolicy::new();
+    let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+
+    assert!(policy.retries(1, &Err(Error::Connection(Box::new(refused)))));
+    assert!(policy.retries(1, &Err(Error::Timeout(Duration::from_secs(1)))));
+    assert!(!policy.retries(1, &Err(Error::Transport("invalid header".into()))));
+    assert!(!policy.retries(1, &Err(Error::InvalidQuery("empty".into()))));
+}
+
+/// A middleware tagging the requests with a header and recording the
+/// order its hooks run in
+struct TagMiddleware {
+    tag: &'static str,
+    calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+impl Middleware for TagMiddleware {
+    fn request(&self, request: &mut transport::Request) -> Result<(), Error> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("request {}", self.tag));
+        request.headers.push(("X-Tag".into(), self.tag.into()));
+        Ok(())
+    }
+
+    fn response(
+        &self,
+        _: &transport::Request,
+        response: &mut transport::Response,
+    ) -> Result<(), Error> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("response {}", self.tag));
+        response.body = serde_json::to_vec(&json!({ "tag": self.tag }))?;
+        Ok(())
+    }
+}
+
+/// A middleware rewriting the request paths
+struct RewriteMiddleware;
+
+impl Middleware for RewriteMiddleware {
+    fn request(&self, request: &mut transport::Request) -> Result<(), Error> {
+        let path = request.url.path().replacen("/db/", "/v2/db/", 1);
+        request.url.set_path(&path);
+        Ok(())
+    }
+}
+
+/// Tests that requests go through the middleware in order and responses in reverse order
+#[async_test]
+async fn middleware_chain() -> Result<(), Exception> {
+    let transport = std::sync::Arc::new(StaticTransport::new(200, json!({})));
+    let calls = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let client = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(transport.clone())
+        .middleware(TagMiddleware {
+            tag: "first",
+            calls: calls.clone(),
+        })
+        .middleware(TagMiddleware {
+            tag: "second",
+            calls: calls.clone(),
+        })
+        .middleware(RewriteMiddleware)
+        .build()?;
+
+    let index = client.get_db_index("docstore").await?;
+
+    // The first middleware sees the response last
+    assert_eq!(index, json!({ "tag": "first" }));
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            "request first",
+            "request second",
+            "response second",
+            "response first"
+        ]
+    );
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(
+        requests[0].url.as_str(),
+        "http://localhost:3000/v2/db/docstore/index"
+    );
+    let tags: Vec<_> = requests[0]
+        .headers
+        .iter()
+        .filter(|(name, _)| name == "X-Tag")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(tags, vec!["first", "second"]);
+    Ok(())
+}
+
+/// A tracing subscriber recording the fields of every span
+#[cfg(feature = "tracing")]
+#[derive(Default)]
+struct FieldRecorder {
+    fields: std::sync::Mutex<Vec<(String, String)>>,
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::field::Visit for &FieldRecorder {
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.fields
+            .lock()
+            .unwrap()
+            .push((field.name().into(), value.into()));
+    }
+
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.fields
+            .lock()
+            .unwrap()
+            .push((field.name().into(), format!("{:?}", value)));
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl tracing::Subscriber for FieldRecorder {
+    fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        span.record(&mut &*self);
+        tracing::span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &tracing::span::Id, values: &tracing::span::Record<'_>) {
+        values.record(&mut &*self);
+    }
+
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+    fn event(&self, _: &tracing::Event<'_>) {}
+
+    fn enter(&self, _: &tracing::span::Id) {}
+
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+/// Tests that requests are recorded in a span with their operation, status and error kind
+#[cfg(feature = "tracing")]
+#[async_test]
+async fn tracing_span() -> Result<(), Exception> {
+    let recorder = std::sync::Arc::new(FieldRecorder::default());
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+    let client = Client::with_transport(
+        url::Url::parse("http://localhost:3000")?,
+        StaticTransport::new(404, json!({ "message": "missing" })),
+    );
+
+    assert!(client.get_db_index("docstore").await.is_err());
+
+    let fields = recorder.fields.lock().unwrap();
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(field("operation"), Some("get_db_index"));
+    assert_eq!(field("dbname"), Some("docstore"));
+    assert_eq!(field("method"), Some("GET"));
+    assert_eq!(field("path"), Some("db/docstore/index"));
+    assert_eq!(field("status"), Some("404"));
+    assert_eq!(field("error"), Some("not_found"));
+    assert!(field("latency_ms").is_some());
+    Ok(())
+}
+
+/// Tests that the requests, failures and bytes of each operation are counted
+#[cfg(feature = "metrics")]
+#[async_test]
+async fn metrics() -> Result<(), Exception> {
+    let registry = prometheus::Registry::new();
+    let client = Client::builder(url::Url::parse("http://localhost:3000")?)
+        .transport(
+            StaticTransport::new(200, json!({ "hash": "zdpu" }))
+                .then(404, json!({ "message": "missing" })),
+        )
+        .metrics(Metrics::new(&registry)?)
+        .build()?;
+
+    client.db_add("eventlog", "entry").await?;
+    assert!(client.db_add("eventlog", "entry").await.is_err());
+
+    let value = |name: &str, labels: &[(&str, &str)]| -> Option<f64> {
+        let family = registry
+            .gather()
+            .into_iter()
+            .find(|family| family.get_name() == name)?;
+        family
+            .get_metric()
+            .iter()
+            .find(|metric| {
+                labels.iter().all(|(label, value)| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .any(|pair| pair.get_name() == *label && pair.get_value() == *value)
+                })
+            })
+            .map(|metric| match family.get_field_type() {
+                prometheus::proto::MetricType::HISTOGRAM => {
+                    metric.get_histogram().get_sample_count() as f64
+                }
+                _ => metric.get_counter().get_value(),
+            })
+    };
+    let add = [("operation", "db_add")];
+    assert_eq!(value("orbitdb_client_requests_total", &add), Some(2.0));
+    assert_eq!(
+        value(
+            "orbitdb_client_request_failures_total",
+            &[("operation", "db_add"), ("kind", "not_found")]
+        ),
+        Some(1.0)
+    );
+    assert_eq!(
+        value("orbitdb_client_request_duration_seconds", &add),
+        Some(2.0)
+    );
+    assert_eq!(value("orbitdb_client_sent_bytes_total", &add), Some(14.0));
+    assert_eq!(
+        value("orbitdb_client_received_bytes_total", &add),
+        Some((br#"{"hash":"zdpu"}"#.len() + br#"{"message":"missing"}"#.len()) as f64)
+    );
+    Ok(())
+}
+
+/// Tests that the interactions recorded by a `RecordingTransport` are
+/// replayed in order by a `ReplayTransport`
+#[async_test]
+async fn record_and_replay() -> Result<(), Exception> {
+    let fixture = std::env::temp_dir().join(format!(
+        "orbit-db-http-client-fixture-{}.json",
+        std::process::id()
+    ));
+    let base_url = url::Url::parse("http://localhost:3000")?;
+
+    let recorder = transport::RecordingTransport::new(
+        StaticTransport::new(200, json!({ "hash": "zdpu1" }))
+            .header("Content-Type", "application/json")
+            .header("set-cookie", "session=secret")
+            .then(200, json!({ "hash": "zdpu2" }))
+            .then(404, json!({ "message": "missing" })),
+        &fixture,
+    );
+    let client = Client::with_transport(base_url.clone(), recorder);
+    client.get_db_index("eventlog").await?;
+    client.get_db_index("eventlog").await?;
+    assert!(client.get_db("fake").await.is_err());
+
+    let recorded: Value = serde_json::from_slice(&std::fs::read(&fixture)?)?;
+    assert_eq!(
+        recorded[0]["request"],
+        json!({ "method": "GET", "path": "/db/eventlog/index" })
+    );
+    assert_eq!(
+        recorded[0]["response"]["headers"],
+        json!([["Content-Type", "application/json"]])
+    );
+
+    let replay = std::sync::Arc::new(transport::ReplayTransport::new(&fixture)?);
+    std::fs::remove_file(&fixture)?;
+    let client = Client::with_transport(base_url, replay.clone());
+    assert!(matches!(
+        client.get_db("fake").await,
+        Err(Error::NotFound(_))
+    ));
+    assert_eq!(
+        client.get_db_index("eventlog").await?,
+        json!({ "hash": "zdpu1" })
+    );
+    assert_eq!(
+        client.get_db_index("eventlog").await?,
+        json!({ "hash": "zdpu2" })
+    );
+    assert!(matches!(
+        client.get_db_index("eventlog").await,
+        Err(Error::Transport(_))
+    ));
+    assert_eq!(replay.remaining(), 0);
+    Ok(())
+}
@@ -0,0 +1,161 @@
+//! The HTTP transports a `Client` sends its requests through
+//!
+//! Transports for surf, reqwest and hyper are available behind the
+//! cargo features of the same names, surf being the default. The `testing`
+//! cargo feature adds transports recording and replaying fixture files
+
+use super::*;
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, TryStreamExt};
+use url::Url;
+
+#[cfg(any(test, feature = "testing"))]
+mod fixtures;
+#[cfg(feature = "hyper")]
+mod hyper_client;
+#[cfg(feature = "reqwest")]
+mod reqwest_client;
+#[cfg(feature = "surf")]
+mod surf_client;
+mod tls;
+
+#[cfg(any(test, feature = "testing"))]
+pub use fixtures::{RecordingTransport, ReplayTransport};
+#[cfg(feature = "hyper")]
+pub use hyper_client::HyperTransport;
+#[cfg(feature = "reqwest")]
+pub use reqwest_client::ReqwestTransport;
+#[cfg(feature = "surf")]
+pub use surf_client::SurfTransport;
+pub use tls::TlsConfig;
+
+/// An HTTP request to be sent by a `Transport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    /// The HTTP method
+    pub method: RequestType,
+    /// The full request url
+    pub url: Url,
+    /// The request headers, as name and value pairs
+    pub headers: Vec<(String, String)>,
+    /// The request body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+/// An HTTP response received by a `Transport`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// The HTTP status code
+    pub status: u16,
+    /// The response headers, as name and value pairs
+    pub headers: Vec<(String, String)>,
+    /// The response body
+    pub body: Vec<u8>,
+}
+
+/// The body of a streamed response, as the chunks received
+pub type BodyStream = BoxStream<'static, Result<Vec<u8>, Error>>;
+
+/// An HTTP response whose body is received as it is streamed
+pub struct StreamingResponse {
+    /// The HTTP status code
+    pub status: u16,
+    /// The response headers, as name and value pairs
+    pub headers: Vec<(String, String)>,
+    /// The response body
+    pub body: BodyStream,
+}
+
+/// Sends the HTTP requests made by a `Client`
+pub trait Transport: Send + Sync {
+    /// Sends `request`, returning the response once its whole body is received
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>>;
+
+    /// Sends `request`, returning the response as soon as its headers are received
+    ///
+    /// Used for server-sent events, whose body does not end. The default
+    /// implementation waits for the whole body, so transports supporting
+    /// subscriptions must override it
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        Box::pin(async move {
+            let Response {
+                status,
+                headers,
+                body,
+            } = self.send(request).await?;
+
+            Ok(StreamingResponse {
+                status,
+                headers,
+                body: Box::pin(stream::once(async { Ok(body) })),
+            })
+        })
+    }
+}
+
+/// Lets a transport be shared, e.g. to inspect it while a `Client` uses it
+impl<T: Transport + ?Sized> Transport for std::sync::Arc<T> {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        (**self).send(request)
+    }
+
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        (**self).send_streaming(request)
+    }
+}
+
+impl Response {
+    /// Gets the value of the header `name`, ignoring its case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl StreamingResponse {
+    /// Receives the rest of the body, for responses that turn out not to be streamed
+    pub async fn into_response(self) -> Result<Response, Error> {
+        let body = self
+            .body
+            .try_fold(vec![], |mut body, chunk| async move {
+                body.extend(chunk);
+                Ok(body)
+            })
+            .await?;
+
+        Ok(Response {
+            status: self.status,
+            headers: self.headers,
+            body,
+        })
+    }
+}
+
+/// The transport used by `Client::new`: surf's if enabled, otherwise reqwest's or hyper's
+#[cfg(any(feature = "surf", feature = "reqwest", feature = "hyper"))]
+pub(crate) fn default_transport() -> std::sync::Arc<dyn Transport> {
+    #[cfg(feature = "surf")]
+    return std::sync::Arc::new(SurfTransport::new());
+    #[cfg(all(not(feature = "surf"), feature = "reqwest"))]
+    return std::sync::Arc::new(ReqwestTransport::new());
+    #[cfg(all(not(feature = "surf"), not(feature = "reqwest")))]
+    return std::sync::Arc::new(HyperTransport::new());
+}
+
+/// The transport used by `ClientBuilder` for custom TLS settings: reqwest's
+/// if enabled, otherwise hyper's, surf's not supporting them
+pub(crate) fn tls_transport(tls: &TlsConfig) -> Result<std::sync::Arc<dyn Transport>, Error> {
+    #[cfg(feature = "reqwest")]
+    return Ok(std::sync::Arc::new(ReqwestTransport::with_tls(tls)?));
+    #[cfg(all(not(feature = "reqwest"), feature = "hyper"))]
+    return Ok(std::sync::Arc::new(HyperTransport::with_tls(tls)?));
+    #[cfg(not(any(feature = "reqwest", feature = "hyper")))]
+    {
+        let _ = tls;
+        Err(Error::Config(
+            "TLS settings require the reqwest or hyper transport".into(),
+        ))
+    }
+}
@@ -0,0 +1,233 @@
+//! Recording of request and response pairs into fixture files, and their replay
+//!
+//! A fixture file is a pretty-printed json array of interactions, so that
+//! changes in the server's responses show up as readable diffs:
+//!
+//! ```json
+//! [
+//!   {
+//!     "request": { "method": "POST", "path": "/db/feed/add", "body": "entry" },
+//!     "response": { "status": 200, "headers": [], "body": { "hash": "zdpu..." } }
+//!   }
+//! ]
+//! ```
+//!
+//! Json bodies are stored as `body`, other bodies as `text`. Request headers
+//! are not recorded, nor are the response headers setting cookies or carrying
+//! authentication info, keeping credentials out of the fixtures
+
+use super::*;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The response headers that may carry credentials, left out of the fixtures
+const SECRET_HEADERS: &[&str] = &["Set-Cookie", "Set-Cookie2", "Authentication-Info"];
+
+/// A request and the response it received
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedRequest {
+    method: String,
+    /// The path and query of the url, without its origin
+    path: String,
+    #[serde(flatten)]
+    body: RecordedBody,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct RecordedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    #[serde(flatten)]
+    body: RecordedBody,
+}
+
+/// A body, stored as json when it is json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct RecordedBody {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    body: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+}
+
+impl RecordedBody {
+    fn new(body: &[u8]) -> Self {
+        if body.is_empty() {
+            return Self::default();
+        }
+        match serde_json::from_slice(body) {
+            // Absent from the file, as null would be read back as no body
+            Ok(Value::Null) => Self::default(),
+            Ok(value) => RecordedBody {
+                body: Some(value),
+                text: None,
+            },
+            Err(_) => RecordedBody {
+                body: None,
+                text: Some(String::from_utf8_lossy(body).into_owned()),
+            },
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        match (&self.body, &self.text) {
+            (Some(value), _) => serde_json::to_vec(value).unwrap_or_default(),
+            (None, Some(text)) => text.clone().into_bytes(),
+            (None, None) => vec![],
+        }
+    }
+}
+
+impl RecordedRequest {
+    fn new(request: &Request) -> Self {
+        let path = match request.url.query() {
+            Some(query) => format!("{}?{}", request.url.path(), query),
+            None => request.url.path().to_string(),
+        };
+        RecordedRequest {
+            method: request.method.as_str().into(),
+            path,
+            body: RecordedBody::new(request.body.as_deref().unwrap_or_default()),
+        }
+    }
+}
+
+/// A transport recording the requests sent through another transport and
+/// their responses into a fixture file
+///
+/// The file is rewritten after each response, so that it is complete even
+/// if a test fails. Streamed responses, such as event subscriptions, are
+/// passed through without being recorded
+pub struct RecordingTransport {
+    inner: Box<dyn Transport>,
+    path: PathBuf,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl RecordingTransport {
+    /// Records the requests sent through `inner` into the fixture file at `path`,
+    /// replacing any previous recording
+    pub fn new(inner: impl Transport + 'static, path: impl AsRef<Path>) -> Self {
+        RecordingTransport {
+            inner: Box::new(inner),
+            path: path.as_ref().to_path_buf(),
+            interactions: Mutex::new(vec![]),
+        }
+    }
+
+    /// Writes the interactions recorded so far to the fixture file
+    fn save(&self, interactions: &[Interaction]) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut json = serde_json::to_vec_pretty(interactions)?;
+        json.push(b'\n');
+        fs::write(&self.path, json)?;
+        Ok(())
+    }
+}
+
+impl Transport for RecordingTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        Box::pin(async move {
+            let recorded = RecordedRequest::new(&request);
+            let response = self.inner.send(request).await?;
+
+            let mut interactions = self.interactions.lock().unwrap();
+            interactions.push(Interaction {
+                request: recorded,
+                response: RecordedResponse {
+                    status: response.status,
+                    headers: response
+                        .headers
+                        .iter()
+                        .filter(|(name, _)| {
+                            !SECRET_HEADERS
+                                .iter()
+                                .any(|secret| name.eq_ignore_ascii_case(secret))
+                        })
+                        .cloned()
+                        .collect(),
+                    body: RecordedBody::new(&response.body),
+                },
+            });
+            self.save(&interactions)?;
+            Ok(response)
+        })
+    }
+
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        self.inner.send_streaming(request)
+    }
+}
+
+/// A transport answering requests with the responses recorded in a fixture file
+///
+/// A request is answered by the first unused interaction with the same
+/// method, path and body, json bodies being compared as json. Each
+/// interaction answers a single request, so repeated requests receive
+/// their responses in the recorded order
+pub struct ReplayTransport {
+    /// The interactions, along with whether they were used
+    interactions: Mutex<Vec<(Interaction, bool)>>,
+}
+
+impl ReplayTransport {
+    /// Loads the fixture file at `path`
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let interactions: Vec<Interaction> = serde_json::from_slice(&fs::read(path)?)?;
+
+        Ok(ReplayTransport {
+            interactions: Mutex::new(
+                interactions
+                    .into_iter()
+                    .map(|interaction| (interaction, false))
+                    .collect(),
+            ),
+        })
+    }
+
+    /// The number of recorded interactions that have not answered a request yet
+    pub fn remaining(&self) -> usize {
+        let interactions = self.interactions.lock().unwrap();
+        interactions.iter().filter(|(_, used)| !used).count()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        let request = RecordedRequest::new(&request);
+        let mut interactions = self.interactions.lock().unwrap();
+        let interaction = interactions
+            .iter_mut()
+            .find(|(interaction, used)| !used && interaction.request == request);
+
+        let response = match interaction {
+            Some((interaction, used)) => {
+                *used = true;
+                let response = &interaction.response;
+                Ok(Response {
+                    status: response.status,
+                    headers: response.headers.clone(),
+                    body: response.body.to_bytes(),
+                })
+            }
+            None => Err(Error::Transport(
+                format!(
+                    "no recorded response left for {} {}",
+                    request.method, request.path
+                )
+                .into(),
+            )),
+        };
+        Box::pin(async move { response })
+    }
+}
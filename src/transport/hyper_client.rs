@@ -0,0 +1,135 @@
+use super::*;
+use futures::StreamExt;
+use hyper::client::HttpConnector;
+use hyper::{Body, Method};
+use hyper_tls::HttpsConnector;
+
+/// A transport sending requests with a `hyper::Client`, on a tokio runtime
+#[derive(Debug, Clone)]
+pub struct HyperTransport {
+    client: hyper::Client<HttpsConnector<HttpConnector>, Body>,
+}
+
+impl HyperTransport {
+    /// The constructor
+    pub fn new() -> Self {
+        HyperTransport {
+            client: hyper::Client::builder().build(HttpsConnector::new()),
+        }
+    }
+
+    /// Creates a transport sending requests with an already configured `client`
+    pub fn with_client(client: hyper::Client<HttpsConnector<HttpConnector>, Body>) -> Self {
+        HyperTransport { client }
+    }
+
+    /// Creates a transport with the root certificates, client identity and
+    /// certificate checks of `tls`
+    pub fn with_tls(tls: &TlsConfig) -> Result<Self, Error> {
+        let mut builder = native_tls::TlsConnector::builder();
+        builder.danger_accept_invalid_certs(tls.accept_invalid_certs);
+        for pem in tls.certificates() {
+            let certificate = native_tls::Certificate::from_pem(&pem).map_err(transport_error)?;
+            builder.add_root_certificate(certificate);
+        }
+        if let Some((cert, key)) = &tls.identity {
+            let identity = native_tls::Identity::from_pkcs8(cert, key).map_err(transport_error)?;
+            builder.identity(identity);
+        }
+        let tls = builder.build().map_err(transport_error)?;
+
+        // The connector must let https urls through to the TLS connector
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        let connector = HttpsConnector::from((http, tokio_native_tls::TlsConnector::from(tls)));
+
+        Ok(Self::with_client(hyper::Client::builder().build(connector)))
+    }
+
+    /// Sends `request`, returning hyper's response once its headers are received
+    async fn respond(&self, request: Request) -> Result<hyper::Response<Body>, Error> {
+        let method = match request.method {
+            RequestType::Get => Method::GET,
+            RequestType::Post => Method::POST,
+            RequestType::Delete => Method::DELETE,
+        };
+
+        let mut req = hyper::Request::builder()
+            .method(method)
+            .uri(request.url.as_str());
+        for (name, value) in request.headers {
+            req = req.header(name.as_str(), value);
+        }
+        let req = req
+            .body(request.body.map_or_else(Body::empty, Body::from))
+            .map_err(transport_error)?;
+
+        self.client.request(req).await.map_err(client_error)
+    }
+}
+
+impl Default for HyperTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transport for HyperTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        Box::pin(async move {
+            let response = self.respond(request).await?;
+            let status = response.status().as_u16();
+            let headers = headers(response.headers());
+            let body = hyper::body::to_bytes(response.into_body())
+                .await
+                .map_err(client_error)?
+                .to_vec();
+
+            Ok(Response {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        Box::pin(async move {
+            let response = self.respond(request).await?;
+            let status = response.status().as_u16();
+            let headers = headers(response.headers());
+            let body = response
+                .into_body()
+                .map(|chunk| chunk.map(|chunk| chunk.to_vec()).map_err(client_error));
+
+            Ok(StreamingResponse {
+                status,
+                headers,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+fn headers(headers: &hyper::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn transport_error(err: impl std::error::Error + Send + Sync + 'static) -> Error {
+    Error::Transport(Box::new(err))
+}
+
+/// Maps the errors of hyper's client, telling apart those of the connection
+fn client_error(err: hyper::Error) -> Error {
+    if err.is_connect() || err.is_closed() || err.is_incomplete_message() || err.is_timeout() {
+        Error::Connection(Box::new(err))
+    } else {
+        transport_error(err)
+    }
+}
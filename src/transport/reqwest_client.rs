@@ -0,0 +1,109 @@
+use super::*;
+use futures::StreamExt;
+use reqwest::Method;
+
+/// A transport sending requests with a `reqwest::Client`, on a tokio runtime
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    /// The constructor
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a transport sending requests with an already configured `client`
+    pub fn with_client(client: reqwest::Client) -> Self {
+        ReqwestTransport { client }
+    }
+
+    /// Creates a transport with the root certificates, client identity and
+    /// certificate checks of `tls`
+    pub fn with_tls(tls: &TlsConfig) -> Result<Self, Error> {
+        let mut builder =
+            reqwest::Client::builder().danger_accept_invalid_certs(tls.accept_invalid_certs);
+        for pem in tls.certificates() {
+            let certificate = reqwest::Certificate::from_pem(&pem).map_err(transport_error)?;
+            builder = builder.add_root_certificate(certificate);
+        }
+        if let Some((cert, key)) = &tls.identity {
+            let identity = reqwest::Identity::from_pkcs8_pem(cert, key).map_err(transport_error)?;
+            builder = builder.identity(identity);
+        }
+
+        Ok(Self::with_client(builder.build().map_err(transport_error)?))
+    }
+
+    /// Sends `request`, returning reqwest's response once its headers are received
+    async fn respond(&self, request: Request) -> Result<reqwest::Response, Error> {
+        let method = match request.method {
+            RequestType::Get => Method::GET,
+            RequestType::Post => Method::POST,
+            RequestType::Delete => Method::DELETE,
+        };
+
+        let mut req = self.client.request(method, request.url);
+        for (name, value) in request.headers {
+            req = req.header(name.as_str(), value);
+        }
+        if let Some(body) = request.body {
+            req = req.body(body);
+        }
+
+        req.send().await.map_err(transport_error)
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        Box::pin(async move {
+            let response = self.respond(request).await?;
+            let status = response.status().as_u16();
+            let headers = headers(response.headers());
+            let body = response.bytes().await.map_err(transport_error)?.to_vec();
+
+            Ok(Response {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        Box::pin(async move {
+            let response = self.respond(request).await?;
+            let status = response.status().as_u16();
+            let headers = headers(response.headers());
+            let body = response
+                .bytes_stream()
+                .map(|chunk| chunk.map(|chunk| chunk.to_vec()).map_err(transport_error));
+
+            Ok(StreamingResponse {
+                status,
+                headers,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+fn headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+fn transport_error(err: reqwest::Error) -> Error {
+    if err.is_connect() || err.is_request() || err.is_body() || err.is_timeout() {
+        Error::Connection(Box::new(err))
+    } else {
+        Error::Transport(Box::new(err))
+    }
+}
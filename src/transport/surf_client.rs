@@ -0,0 +1,124 @@
+use super::*;
+use futures::future::poll_fn;
+use futures_io::AsyncRead;
+use std::pin::Pin;
+use surf::http::Method;
+use surf::middleware::{HttpClient, Middleware, Next};
+
+/// A transport sending requests with surf's native HTTP client
+#[derive(Debug, Clone, Default)]
+pub struct SurfTransport;
+
+impl SurfTransport {
+    /// The constructor
+    pub fn new() -> Self {
+        SurfTransport
+    }
+
+    /// Sends `request`, returning surf's response once its headers are received
+    async fn respond(
+        request: Request,
+    ) -> Result<(u16, Vec<(String, String)>, surf::Response), Error> {
+        let method = match request.method {
+            RequestType::Get => Method::GET,
+            RequestType::Post => Method::POST,
+            RequestType::Delete => Method::DELETE,
+        };
+
+        // surf 1.0 only sets headers with static names itself
+        let mut req =
+            surf::Request::new(method, request.url).middleware(SetHeaders(request.headers));
+        if let Some(body) = request.body {
+            req = req.body_bytes(body);
+        }
+
+        let mut response = req.await.map_err(surf_error)?;
+        let headers = response
+            .headers()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+
+        Ok((response.status().as_u16(), headers, response))
+    }
+}
+
+impl Transport for SurfTransport {
+    fn send(&self, request: Request) -> BoxFuture<'_, Result<Response, Error>> {
+        Box::pin(async move {
+            let (status, headers, mut response) = Self::respond(request).await?;
+            let body = response.body_bytes().await.map_err(connection_error)?;
+
+            Ok(Response {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+
+    fn send_streaming(&self, request: Request) -> BoxFuture<'_, Result<StreamingResponse, Error>> {
+        Box::pin(async move {
+            let (status, headers, response) = Self::respond(request).await?;
+            let body = stream::unfold(Some(response), |response| async move {
+                let mut response = response?;
+                let mut buf = vec![0; 4096];
+                let read = poll_fn(|cx| Pin::new(&mut response).poll_read(cx, &mut buf)).await;
+
+                match read {
+                    Ok(0) => None,
+                    Ok(read) => {
+                        buf.truncate(read);
+                        Some((Ok(buf), Some(response)))
+                    }
+                    Err(err) => Some((Err(connection_error(err)), None)),
+                }
+            });
+
+            Ok(StreamingResponse {
+                status,
+                headers,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+/// Sets the request headers on the underlying `http::Request`
+struct SetHeaders(Vec<(String, String)>);
+
+impl<C: HttpClient> Middleware<C> for SetHeaders {
+    fn handle<'a>(
+        &'a self,
+        mut req: surf::middleware::Request,
+        client: C,
+        next: Next<'a, C>,
+    ) -> BoxFuture<'a, Result<surf::middleware::Response, surf::Exception>> {
+        for (name, value) in &self.0 {
+            let name = surf::http::header::HeaderName::from_bytes(name.as_bytes());
+            let value = surf::http::header::HeaderValue::from_str(value);
+
+            match (name, value) {
+                (Ok(name), Ok(value)) => {
+                    req.headers_mut().insert(name, value);
+                }
+                (Err(err), _) => return Box::pin(async move { Err(err.into()) }),
+                (_, Err(err)) => return Box::pin(async move { Err(err.into()) }),
+            }
+        }
+        next.run(req, client)
+    }
+}
+
+/// Maps surf's errors, telling apart the I/O errors of the connection
+fn surf_error(err: surf::Exception) -> Error {
+    if err.is::<std::io::Error>() {
+        Error::Connection(err)
+    } else {
+        Error::Transport(err)
+    }
+}
+
+fn connection_error(err: std::io::Error) -> Error {
+    Error::Connection(Box::new(err))
+}
@@ -0,0 +1,89 @@
+use super::*;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+/// The TLS settings of the reqwest and hyper transports
+///
+/// Certificates and keys are PEM encoded, a root certificate possibly
+/// being a bundle of several certificates
+#[derive(Clone, Default)]
+pub struct TlsConfig {
+    /// Root certificates trusted along with the system's
+    pub root_certificates: Vec<Vec<u8>>,
+    /// The client certificate and its PKCS #8 private key, for mutual TLS
+    pub identity: Option<(Vec<u8>, Vec<u8>)>,
+    /// Whether to accept any server certificate, self-signed or invalid
+    pub accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    /// Settings trusting only the system's root certificates
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trusts the root certificates of the PEM bundle `pem`
+    pub fn add_root_certificate(mut self, pem: &[u8]) -> Self {
+        self.root_certificates.push(pem.to_vec());
+        self
+    }
+
+    /// Trusts the root certificates of the PEM bundle at `path`
+    pub fn add_root_certificate_file(self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        Ok(self.add_root_certificate(&fs::read(path)?))
+    }
+
+    /// Presents the client certificate `cert` with its PKCS #8 private key `key`
+    pub fn identity(mut self, cert: &[u8], key: &[u8]) -> Self {
+        self.identity = Some((cert.to_vec(), key.to_vec()));
+        self
+    }
+
+    /// Presents the client certificate at `cert` with the PKCS #8 private key at `key`
+    pub fn identity_files(
+        self,
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        Ok(self.identity(&fs::read(cert)?, &fs::read(key)?))
+    }
+
+    /// Accepts any server certificate when `accept` is true
+    ///
+    /// This leaves the connection open to interception, so it is only
+    /// meant for development servers with self-signed certificates
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.accept_invalid_certs = accept;
+        self
+    }
+
+    /// Each root certificate, bundles being split into their certificates
+    #[cfg(any(feature = "reqwest", feature = "hyper"))]
+    pub(crate) fn certificates(&self) -> Vec<Vec<u8>> {
+        const END: &str = "-----END CERTIFICATE-----";
+
+        self.root_certificates
+            .iter()
+            .flat_map(|bundle| {
+                let bundle = String::from_utf8_lossy(bundle);
+                bundle
+                    .split_inclusive(END)
+                    .filter(|pem| pem.contains(END))
+                    .map(|pem| pem.trim().as_bytes().to_vec())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Keeps the private key out of logs
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("root_certificates", &self.root_certificates.len())
+            .field("identity", &self.identity.is_some())
+            .field("accept_invalid_certs", &self.accept_invalid_certs)
+            .finish()
+    }
+}